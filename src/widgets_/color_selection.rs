@@ -0,0 +1,339 @@
+use super::selection::{self, WeakSelection};
+use gio::Settings;
+use gtk::prelude::*;
+use std::{
+    cell::{Cell, RefCell},
+    f64::consts::PI,
+    rc::Rc,
+};
+
+/// A radio group of color swatches, such as for choosing an accent color.
+///
+/// Swatches are drawn as circles or squares of the requested size, and the active swatch is
+/// marked with a checkmark. Each swatch is named after its variant in its tooltip, and to
+/// assistive technologies, which see it as a radio button.
+///
+/// Otherwise, the selection behaves as the radio group of an `ImageSelection`: callbacks are
+/// only invoked for changes of the active swatch, keyboard navigation selects the swatch which
+/// receives the focus, and the widget owns the state of the selection until it is destroyed.
+#[derive(AsRef, Deref)]
+pub struct ColorSelection<T: 'static> {
+    #[as_ref]
+    #[deref]
+    container: gtk::FlowBox,
+
+    inner: Rc<Inner<T>>,
+}
+
+impl<T: 'static> Clone for ColorSelection<T> {
+    fn clone(&self) -> Self {
+        Self { container: self.container.clone(), inner: self.inner.clone() }
+    }
+}
+
+impl<T: Clone + 'static> ColorSelection<T> {
+    pub fn new(
+        variants: &[ColorVariant<T>],
+        shape: SwatchShape,
+        size: i32,
+        event_cb: impl Fn(T) + 'static,
+    ) -> Self {
+        let container = gtk::FlowBoxBuilder::new()
+            .can_focus(true)
+            .focus_on_click(false)
+            .homogeneous(true)
+            .column_spacing(6)
+            .row_spacing(6)
+            .selection_mode(gtk::SelectionMode::None)
+            .build();
+
+        let inner = Rc::new(Inner {
+            container:  container.downgrade(),
+            swatches:   RefCell::new(Vec::with_capacity(variants.len())),
+            active:     Cell::new(None),
+            silent:     Cell::new(true),
+            changed_cb: RefCell::new(Vec::new()),
+        });
+
+        for (index, variant) in variants.iter().enumerate() {
+            let color = variant.color;
+            let inner_ = Rc::downgrade(&inner);
+            let area = cascade! {
+                gtk::DrawingArea::new();
+                ..set_size_request(size, size);
+                ..connect_draw(move |area, cr| {
+                    let active = inner_
+                        .upgrade()
+                        .map_or(false, |inner| inner.active.get() == Some(index));
+
+                    if let Err(why) = draw_swatch(area, cr, &color, shape, active) {
+                        error!("failed to draw color swatch: {}", why);
+                    }
+
+                    gtk::Inhibit(false)
+                });
+            };
+
+            let child = cascade! {
+                gtk::FlowBoxChild::new();
+                ..set_can_focus(true);
+                ..set_tooltip_text(Some(variant.name));
+                ..add(&area);
+                ..show_all();
+            };
+
+            if let Some(accessible) = child.accessible() {
+                accessible.set_role(atk::Role::RadioButton);
+                accessible.set_name(variant.name);
+            }
+
+            let inner_ = Rc::downgrade(&inner);
+            selection::select_on_focus(&child, move || {
+                if let Some(inner) = inner_.upgrade() {
+                    inner.select(index);
+                }
+            });
+
+            container.add(&child);
+            inner.swatches.borrow_mut().push(Swatch { child, area, event: variant.event.clone() });
+        }
+
+        // The first swatch of a radio group is active by default.
+        if !variants.is_empty() {
+            inner.select(variants.iter().position(|variant| variant.active).unwrap_or(0));
+        }
+
+        inner.silent.set(false);
+        inner.relate_group();
+
+        let inner_ = Rc::downgrade(&inner);
+        container.connect_child_activated(move |_, child| {
+            if let Some(inner) = inner_.upgrade() {
+                let index = inner.swatches.borrow().iter().position(|s| &s.child == child);
+                if let Some(index) = index {
+                    inner.select(index);
+                }
+            }
+        });
+
+        let inner_ = Rc::downgrade(&inner);
+        selection::enter_on_active(&container, move || {
+            inner_.upgrade().and_then(|inner| inner.active_child())
+        });
+
+        // The swatches are released along with the widget.
+        let inner_ = RefCell::new(Some(inner.clone()));
+        container.connect_destroy(move |_| {
+            if let Some(inner) = inner_.borrow_mut().take() {
+                inner.swatches.borrow_mut().clear();
+            }
+        });
+
+        let selection = Self { container, inner };
+        selection.connect_changed(move |_, event| event_cb(event));
+        selection
+    }
+
+    /// The event of the active swatch, if there are any swatches.
+    pub fn active(&self) -> Option<T> {
+        let index = self.inner.active.get()?;
+        self.inner.swatches.borrow().get(index).map(|swatch| swatch.event.clone())
+    }
+
+    /// Activates the swatch associated with `event`, invoking the change callbacks if another
+    /// swatch was active.
+    ///
+    /// Returns `false` if no swatch is associated with the given event.
+    pub fn set_active(&self, event: T) -> bool
+    where
+        T: PartialEq,
+    {
+        let index = self.inner.swatches.borrow().iter().position(|swatch| swatch.event == event);
+
+        match index {
+            Some(index) => {
+                self.inner.select(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Activates the swatch associated with `event` without invoking any callbacks.
+    pub fn set_active_silent(&self, event: T) -> bool
+    where
+        T: PartialEq,
+    {
+        let silent = self.inner.silent.replace(true);
+        let found = self.set_active(event);
+        self.inner.silent.set(silent);
+        found
+    }
+
+    /// Invoked with the previous and the new event whenever the active swatch changes.
+    pub fn connect_changed<F: Fn(Option<T>, T) + 'static>(&self, func: F) {
+        self.inner.changed_cb.borrow_mut().push(Box::new(func));
+    }
+
+    /// Binds the selection to a string or enum GSettings key, in both directions, as with the
+    /// radio group of an `ImageSelection`.
+    pub fn bind_settings(&self, settings: &Settings, key: &str, values: &[(T, &str)])
+    where
+        T: PartialEq,
+    {
+        let weak = self.downgrade();
+        let write = selection::bind_settings(
+            &self.container,
+            settings,
+            key,
+            values,
+            false,
+            move |events| {
+                let selection = weak.upgrade().map(Self::from_parts);
+                if let (Some(selection), Some(event)) = (selection, events.first()) {
+                    selection.set_active_silent(event.clone());
+                }
+            },
+        );
+
+        self.connect_changed(move |_, event| write(&[event]));
+    }
+
+    /// A reference to the color selection which keeps neither its widget nor its swatches alive.
+    fn downgrade(&self) -> WeakSelection<gtk::FlowBox, Inner<T>> {
+        WeakSelection::new(&self.container, &self.inner)
+    }
+
+    fn from_parts((container, inner): (gtk::FlowBox, Rc<Inner<T>>)) -> Self {
+        Self { container, inner }
+    }
+}
+
+struct Inner<T> {
+    container:  glib::WeakRef<gtk::FlowBox>,
+    swatches:   RefCell<Vec<Swatch<T>>>,
+    active:     Cell<Option<usize>>,
+    silent:     Cell<bool>,
+    changed_cb: RefCell<Vec<Box<dyn Fn(Option<T>, T)>>>,
+}
+
+impl<T: Clone> Inner<T> {
+    /// Activates the swatch at `index`, and notifies the callbacks of the change.
+    fn select(&self, index: usize) {
+        let previous = self.active.replace(Some(index));
+        if previous == Some(index) {
+            return;
+        }
+
+        let (previous, event, child) = {
+            let swatches = self.swatches.borrow();
+
+            let changed = previous.map(|p| (p, false)).into_iter().chain(Some((index, true)));
+            for (position, checked) in changed {
+                if let Some(swatch) = swatches.get(position) {
+                    swatch.area.queue_draw();
+                    if let Some(accessible) = swatch.child.accessible() {
+                        accessible.notify_state_change(atk::State::Checked, checked);
+                    }
+                }
+            }
+
+            let swatch = &swatches[index];
+            let previous = previous.map(|p| swatches[p].event.clone());
+            (previous, swatch.event.clone(), swatch.child.clone())
+        };
+
+        selection::follow_focus(&self.container, &child);
+
+        if self.silent.get() {
+            return;
+        }
+
+        for func in self.changed_cb.borrow().iter() {
+            func(previous.clone(), event.clone());
+        }
+    }
+
+    /// The flow box child of the active swatch.
+    fn active_child(&self) -> Option<gtk::FlowBoxChild> {
+        let index = self.active.get()?;
+        self.swatches.borrow().get(index).map(|swatch| swatch.child.clone())
+    }
+
+    /// Relates the swatches to each other as members of the same radio group.
+    fn relate_group(&self) {
+        selection::relate_group(self.swatches.borrow().iter().map(|swatch| &swatch.child));
+    }
+}
+
+/// The widgets associated with a variant in the selection.
+struct Swatch<T> {
+    child: gtk::FlowBoxChild,
+    area:  gtk::DrawingArea,
+    event: T,
+}
+
+/// Draws a swatch centered within its area, marked with a checkmark if it is active.
+fn draw_swatch(
+    area: &gtk::DrawingArea,
+    cr: &cairo::Context,
+    color: &gdk::RGBA,
+    shape: SwatchShape,
+    active: bool,
+) -> Result<(), cairo::Error> {
+    let width = f64::from(area.allocated_width());
+    let height = f64::from(area.allocated_height());
+
+    // Leaves room for the border to be drawn within the area.
+    let side = width.min(height) - 2.0;
+    let (x, y) = ((width - side) / 2.0, (height - side) / 2.0);
+
+    match shape {
+        SwatchShape::Round => cr.arc(x + side / 2.0, y + side / 2.0, side / 2.0, 0.0, 2.0 * PI),
+        SwatchShape::Square => cr.rectangle(x, y, side, side),
+    }
+
+    cr.set_source_rgba(color.red, color.green, color.blue, color.alpha);
+    cr.fill_preserve()?;
+
+    // A faint border distinguishes swatches which match the background.
+    cr.set_source_rgba(0.0, 0.0, 0.0, 0.15);
+    cr.set_line_width(1.0);
+    cr.stroke()?;
+
+    if active {
+        // The checkmark contrasts with the color of the swatch.
+        let luminance = 0.2126 * color.red + 0.7152 * color.green + 0.0722 * color.blue;
+        let mark = if luminance > 0.5 { 0.0 } else { 1.0 };
+
+        cr.set_source_rgb(mark, mark, mark);
+        cr.set_line_width(side / 10.0);
+        cr.set_line_cap(cairo::LineCap::Round);
+        cr.set_line_join(cairo::LineJoin::Round);
+        cr.move_to(x + side * 0.28, y + side * 0.52);
+        cr.line_to(x + side * 0.44, y + side * 0.68);
+        cr.line_to(x + side * 0.72, y + side * 0.36);
+        cr.stroke()?;
+    }
+
+    Ok(())
+}
+
+/// The shape which color swatches are drawn as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwatchShape {
+    Round,
+    Square,
+}
+
+impl Default for SwatchShape {
+    fn default() -> Self { SwatchShape::Round }
+}
+
+pub struct ColorVariant<'a, T> {
+    /// Names the color in its tooltip, and to assistive technologies.
+    pub name:   &'a str,
+    pub color:  gdk::RGBA,
+    pub active: bool,
+    pub event:  T,
+}
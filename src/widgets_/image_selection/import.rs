@@ -0,0 +1,207 @@
+//! Importing of image files as new variants of the `ImageSelection`.
+
+use super::{ImageSelection, ImageSrcBuf, SelectionVariantBuf, SelectionVariantBuilder};
+use gtk::prelude::*;
+use std::rc::Rc;
+
+/// Creates variants from image files which are imported into the selection.
+pub(super) struct Importer<T> {
+    create:       Rc<dyn Fn(&gio::File) -> Option<T>>,
+    group:        Option<String>,
+    size_request: Option<(i32, i32)>,
+}
+
+impl<T: Clone + PartialEq + 'static> ImageSelection<T> {
+    /// Allows image files to be imported as new variants by dropping them onto the selection.
+    ///
+    /// `create` is invoked with each image file, and returns the event of its new variant, or
+    /// `None` to reject the file. Imported variants are named after their files, are added to
+    /// the section of `group` with the given `size_request`, and become active.
+    ///
+    /// Enabling import again replaces `create`, `group`, and `size_request`.
+    pub fn enable_import<F: Fn(&gio::File) -> Option<T> + 'static>(
+        &self,
+        group: Option<&str>,
+        size_request: Option<(i32, i32)>,
+        create: F,
+    ) {
+        let importer =
+            Importer { create: Rc::new(create), group: group.map(String::from), size_request };
+
+        if self.inner.importer.replace(Some(importer)).is_some() {
+            return;
+        }
+
+        self.container.drag_dest_set(
+            gtk::DestDefaults::ALL,
+            &[gtk::TargetEntry::new("text/uri-list", gtk::TargetFlags::OTHER_APP, 0)],
+            gdk::DragAction::COPY,
+        );
+
+        let selection = self.downgrade();
+        self.container.connect_drag_data_received(move |_, _, _, _, data, _, _| {
+            if let Some(selection) = selection.upgrade().map(Self::from_parts) {
+                for uri in data.uris() {
+                    selection.import(&gio::File::for_uri(&uri));
+                }
+            }
+        });
+    }
+
+    /// Appends a tile to the section of imported variants, which opens a file chooser to
+    /// import images.
+    ///
+    /// Has no effect unless importing has been enabled with `enable_import`, or if the section
+    /// already has a tile.
+    pub fn add_import_tile(&self, label: &str) {
+        let (group, size_request) = match self.inner.importer.borrow().as_ref() {
+            Some(importer) => (importer.group.clone(), importer.size_request),
+            None => return,
+        };
+
+        let has_tile = self
+            .inner
+            .sections
+            .borrow()
+            .iter()
+            .any(|section| section.name == group && section.tile.is_some());
+
+        if has_tile {
+            return;
+        }
+
+        let image = gtk::Image::from_icon_name(Some("list-add-symbolic"), gtk::IconSize::Dialog);
+
+        if let Some((width, height)) = size_request {
+            image.set_size_request(width, height);
+        }
+
+        let child = cascade! {
+            gtk::FlowBoxChild::new();
+            ..set_can_focus(true);
+            ..add(&cascade! {
+                gtk::Box::new(gtk::Orientation::Vertical, 12);
+                ..add(&image);
+                ..add(&gtk::LabelBuilder::new().label(label).halign(gtk::Align::Center).build());
+            });
+            ..show_all();
+        };
+
+        if let Some(accessible) = child.accessible() {
+            accessible.set_role(atk::Role::PushButton);
+            accessible.set_name(label);
+        }
+
+        let flow_box = super::Inner::section(&self.inner, group.as_deref());
+        flow_box.add(&child);
+
+        for section in self.inner.sections.borrow_mut().iter_mut() {
+            if section.flow_box == flow_box {
+                section.set_visible(true);
+                section.tile = Some(child.clone());
+            }
+        }
+
+        let selection = self.downgrade();
+        let title = label.to_owned();
+        flow_box.connect_child_activated(move |_, activated| {
+            if activated != &child {
+                return;
+            }
+
+            if let Some(selection) = selection.upgrade().map(Self::from_parts) {
+                for file in choose_images(activated, &title) {
+                    selection.import(&file);
+                }
+            }
+        });
+    }
+
+    /// Creates a variant for an image file, and activates it.
+    ///
+    /// Returns `false` if the file is not an image, or was rejected.
+    fn import(&self, file: &gio::File) -> bool {
+        let basename = match file.basename() {
+            Some(basename) => basename,
+            None => return false,
+        };
+
+        if !is_image(file) {
+            return false;
+        }
+
+        // The importer is not borrowed while creating the event, so that `create` may modify
+        // the selection.
+        let (create, group, size_request) = match self.inner.importer.borrow().as_ref() {
+            Some(importer) => {
+                (importer.create.clone(), importer.group.clone(), importer.size_request)
+            }
+            None => return false,
+        };
+
+        let event = match create(file) {
+            Some(event) => event,
+            None => return false,
+        };
+
+        let name = basename.file_stem().unwrap_or_else(|| basename.as_os_str()).to_string_lossy();
+
+        self.push(SelectionVariantBuf {
+            group,
+            size_request,
+            ..SelectionVariantBuilder::new(name, event)
+                .image(ImageSrcBuf::GFile(file.clone()))
+                .active(true)
+                .build()
+        });
+
+        true
+    }
+}
+
+/// Whether the file is an image, by the content type of the file; or by its name if the
+/// content type cannot be queried.
+fn is_image(file: &gio::File) -> bool {
+    let content_type = file
+        .query_info(
+            "standard::content-type",
+            gio::FileQueryInfoFlags::NONE,
+            None::<&gio::Cancellable>,
+        )
+        .ok()
+        .and_then(|info| info.content_type())
+        .or_else(|| {
+            let basename = file.basename()?;
+            Some(gio::content_type_guess(basename.to_str(), &[]).0)
+        });
+
+    content_type
+        .and_then(|content_type| gio::content_type_get_mime_type(&content_type))
+        .map_or(false, |mime| mime.starts_with("image/"))
+}
+
+/// Asks the user to choose image files with the native file chooser.
+fn choose_images(widget: &impl IsA<gtk::Widget>, title: &str) -> Vec<gio::File> {
+    let parent = widget.toplevel().and_then(|toplevel| toplevel.downcast::<gtk::Window>().ok());
+
+    let filter = gtk::FileFilter::new();
+    filter.set_name(Some(title));
+    filter.add_pixbuf_formats();
+
+    let chooser = gtk::FileChooserNative::new(
+        Some(title),
+        parent.as_ref(),
+        gtk::FileChooserAction::Open,
+        None,
+        None,
+    );
+
+    chooser.set_select_multiple(true);
+    chooser.add_filter(&filter);
+
+    let files =
+        if chooser.run() == gtk::ResponseType::Accept { chooser.files() } else { Vec::new() };
+
+    chooser.destroy();
+    files
+}
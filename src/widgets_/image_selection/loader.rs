@@ -0,0 +1,302 @@
+//! Loading of images for the `ImageSelection`.
+
+use super::{ImageFit, ImageSrc, ImageSrcBuf};
+use gdk_pixbuf::{Pixbuf, PixbufAnimation};
+use gtk::prelude::*;
+use std::time::SystemTime;
+
+/// The size of icons which have no requested size.
+const DEFAULT_ICON_SIZE: i32 = 48;
+
+/// The source of an image, which is retained for loading images after construction.
+#[derive(Clone)]
+pub(super) enum ImageSource {
+    File(gio::File),
+    Bytes(glib::Bytes),
+    Icon(gio::Icon),
+    Pixbuf(Pixbuf),
+    Animation(PixbufAnimation),
+}
+
+impl From<&ImageSrcBuf> for ImageSource {
+    fn from(src: &ImageSrcBuf) -> Self {
+        match src {
+            ImageSrcBuf::File(path) => ImageSource::File(gio::File::for_path(path)),
+            ImageSrcBuf::Resource(res) => {
+                ImageSource::File(gio::File::for_uri(&["resource://", res].concat()))
+            }
+            ImageSrcBuf::Uri(uri) => ImageSource::File(gio::File::for_uri(uri)),
+            ImageSrcBuf::GFile(file) => ImageSource::File(file.clone()),
+            ImageSrcBuf::Bytes(bytes) => ImageSource::Bytes(bytes.clone()),
+            ImageSrcBuf::IconName(name) => ImageSource::Icon(gio::ThemedIcon::new(name).upcast()),
+            ImageSrcBuf::Icon(icon) => ImageSource::Icon(icon.clone()),
+            ImageSrcBuf::Pixbuf(pixbuf) => ImageSource::Pixbuf(pixbuf.clone()),
+            ImageSrcBuf::Animation(animation) => ImageSource::Animation(animation.clone()),
+        }
+    }
+}
+
+impl<'a> From<ImageSrc<'a>> for ImageSource {
+    fn from(src: ImageSrc<'a>) -> Self { ImageSource::from(&ImageSrcBuf::from(src)) }
+}
+
+impl ImageSource {
+    /// Decodes the image on the current thread.
+    pub fn load(&self) -> Result<Pixbuf, glib::Error> {
+        match self {
+            ImageSource::File(file) => Pixbuf::from_stream(
+                &file.read(None::<&gio::Cancellable>)?,
+                None::<&gio::Cancellable>,
+            ),
+            ImageSource::Bytes(bytes) => Pixbuf::from_stream(
+                &gio::MemoryInputStream::from_bytes(bytes),
+                None::<&gio::Cancellable>,
+            ),
+            ImageSource::Icon(icon) => lookup_icon(icon, DEFAULT_ICON_SIZE)?.load_icon(),
+            ImageSource::Pixbuf(pixbuf) => Ok(pixbuf.clone()),
+            ImageSource::Animation(animation) => first_frame(animation),
+        }
+    }
+
+    /// Reads, decodes, and scales the image to `size` in a background thread.
+    ///
+    /// A dimension of `-1` leaves that side unconstrained. The `func` is invoked on the main
+    /// context once the image has been loaded, or the load has failed or was cancelled.
+    /// Icons are square, and are therefore always fit to the smaller side. Pixbufs have
+    /// already been decoded, so they are scaled on the current thread. Only the first frame of
+    /// an animation is loaded.
+    pub fn load_async<F: FnOnce(Result<Pixbuf, glib::Error>) + 'static>(
+        &self,
+        (width, height): (i32, i32),
+        fit: ImageFit,
+        cancellable: &gio::Cancellable,
+        func: F,
+    ) {
+        match self {
+            ImageSource::Pixbuf(pixbuf) => func(Ok(scale(pixbuf, (width, height), fit))),
+            ImageSource::Animation(animation) => {
+                func(first_frame(animation).map(|pixbuf| scale(&pixbuf, (width, height), fit)))
+            }
+            ImageSource::Icon(icon) => {
+                match lookup_icon(icon, icon_size(width, height)) {
+                    Ok(info) => info.load_icon_async(Some(cancellable), func),
+                    Err(why) => func(Err(why)),
+                }
+            }
+            _ if fit == ImageFit::Fit || width < 1 || height < 1 => {
+                self.decode_async((width, height), cancellable, func)
+            }
+            _ => {
+                // The aspect ratio is unknown until decoded, so the image is first scaled to the
+                // requested width, and scaled again to the requested height if it is then too
+                // short.
+                let source = self.clone();
+                let cancellable_ = cancellable.clone();
+                self.decode_async((width, -1), cancellable, move |result| match result {
+                    Ok(pixbuf) if pixbuf.height() >= height => {
+                        func(Ok(crop(pixbuf, width, height)))
+                    }
+                    Ok(_) => source.decode_async((-1, height), &cancellable_, move |result| {
+                        func(result.map(|pixbuf| crop(pixbuf, width, height)))
+                    }),
+                    Err(why) => func(Err(why)),
+                });
+            }
+        }
+    }
+
+    /// Decodes and scales an encoded image in a background thread.
+    fn decode_async<F: FnOnce(Result<Pixbuf, glib::Error>) + 'static>(
+        &self,
+        (width, height): (i32, i32),
+        cancellable: &gio::Cancellable,
+        func: F,
+    ) {
+        match self {
+            ImageSource::File(file) => {
+                let cancellable_ = cancellable.clone();
+                file.read_async(glib::PRIORITY_LOW, Some(cancellable), move |result| match result {
+                    Ok(stream) => {
+                        decode_stream_async(&stream, (width, height), &cancellable_, func)
+                    }
+                    Err(why) => func(Err(why)),
+                });
+            }
+            ImageSource::Bytes(bytes) => decode_stream_async(
+                &gio::MemoryInputStream::from_bytes(bytes),
+                (width, height),
+                cancellable,
+                func,
+            ),
+            ImageSource::Icon(_) | ImageSource::Pixbuf(_) | ImageSource::Animation(_) => {
+                self.load_async((width, height), ImageFit::Fit, cancellable, func)
+            }
+        }
+    }
+}
+
+/// Decodes and scales the contents of a stream in a background thread.
+fn decode_stream_async<S, F>(
+    stream: &S,
+    (width, height): (i32, i32),
+    cancellable: &gio::Cancellable,
+    func: F,
+) where
+    S: IsA<gio::InputStream>,
+    F: FnOnce(Result<Pixbuf, glib::Error>) + 'static,
+{
+    Pixbuf::from_stream_at_scale_async(stream, width, height, true, Some(cancellable), func)
+}
+
+/// The frame which is displayed while an animation is paused.
+fn first_frame(animation: &PixbufAnimation) -> Result<Pixbuf, glib::Error> {
+    animation.static_image().ok_or_else(|| {
+        glib::Error::new(gdk_pixbuf::PixbufError::CorruptImage, "animation has no frames")
+    })
+}
+
+/// Plays the animation on the image, scaling each frame to `size` as the animation advances.
+///
+/// The animation plays until the returned callback is removed, or the image is destroyed.
+pub(super) fn play(
+    animation: &PixbufAnimation,
+    image: &gtk::Image,
+    size: (i32, i32),
+    fit: ImageFit,
+    scale: i32,
+) -> gtk::TickCallbackId {
+    let frames = animation.iter(Some(SystemTime::now()));
+    show_frame(image, &frames.pixbuf(), size, fit, scale);
+
+    image.add_tick_callback(move |image, _| {
+        if frames.advance(SystemTime::now()) {
+            show_frame(image, &frames.pixbuf(), size, fit, scale);
+        }
+
+        glib::Continue(true)
+    })
+}
+
+/// Displays a frame of an animation on the image, scaled to `size`.
+fn show_frame(image: &gtk::Image, frame: &Pixbuf, size: (i32, i32), fit: ImageFit, scale: i32) {
+    let surface = self::scale(frame, size, fit).create_surface(scale, image.window().as_ref());
+    image.set_from_surface(surface.as_ref());
+}
+
+/// Looks up an icon of the given size in the default icon theme.
+fn lookup_icon(icon: &gio::Icon, size: i32) -> Result<gtk::IconInfo, glib::Error> {
+    gtk::IconTheme::default()
+        .and_then(|theme| theme.lookup_by_gicon(icon, size, gtk::IconLookupFlags::FORCE_SIZE))
+        .ok_or_else(|| {
+            glib::Error::new(gtk::IconThemeError::NotFound, "icon not found in the icon theme")
+        })
+}
+
+/// The size of a square icon which fits within the requested size.
+fn icon_size(width: i32, height: i32) -> i32 {
+    match (width > 0, height > 0) {
+        (true, true) => width.min(height),
+        (true, false) => width,
+        (false, true) => height,
+        (false, false) => DEFAULT_ICON_SIZE,
+    }
+}
+
+/// Scales a decoded image to the requested size.
+fn scale(pixbuf: &Pixbuf, (width, height): (i32, i32), fit: ImageFit) -> Pixbuf {
+    let ratio = |side: i32, actual: i32| {
+        if side > 0 {
+            Some(f64::from(side) / f64::from(actual))
+        } else {
+            None
+        }
+    };
+
+    let ratio = match (ratio(width, pixbuf.width()), ratio(height, pixbuf.height())) {
+        (Some(x), Some(y)) if fit == ImageFit::Crop => x.max(y),
+        (Some(x), Some(y)) => x.min(y),
+        (Some(ratio), None) | (None, Some(ratio)) => ratio,
+        (None, None) => return pixbuf.clone(),
+    };
+
+    let scaled_side = |side: i32| ((f64::from(side) * ratio).round() as i32).max(1);
+
+    let scaled = pixbuf
+        .scale_simple(
+            scaled_side(pixbuf.width()),
+            scaled_side(pixbuf.height()),
+            gdk_pixbuf::InterpType::Bilinear,
+        )
+        .unwrap_or_else(|| pixbuf.clone());
+
+    if fit == ImageFit::Crop && width > 0 && height > 0 {
+        crop(scaled, width, height)
+    } else {
+        scaled
+    }
+}
+
+/// Crops the overflowing edges of the image, keeping it centered.
+fn crop(pixbuf: Pixbuf, width: i32, height: i32) -> Pixbuf {
+    let width = width.min(pixbuf.width());
+    let height = height.min(pixbuf.height());
+    let x = (pixbuf.width() - width) / 2;
+    let y = (pixbuf.height() - height) / 2;
+
+    pixbuf.new_subpixbuf(x, y, width, height).unwrap_or(pixbuf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixbuf(width: i32, height: i32) -> Pixbuf {
+        Pixbuf::new(gdk_pixbuf::Colorspace::Rgb, false, 8, width, height)
+            .expect("failed to allocate pixbuf")
+    }
+
+    fn size_of(pixbuf: &Pixbuf) -> (i32, i32) { (pixbuf.width(), pixbuf.height()) }
+
+    #[test]
+    fn fit_scales_within_the_requested_size() {
+        assert_eq!(size_of(&scale(&pixbuf(400, 200), (100, 100), ImageFit::Fit)), (100, 50));
+        assert_eq!(size_of(&scale(&pixbuf(200, 400), (100, 100), ImageFit::Fit)), (50, 100));
+        assert_eq!(size_of(&scale(&pixbuf(50, 25), (100, 100), ImageFit::Fit)), (100, 50));
+    }
+
+    #[test]
+    fn crop_covers_and_crops_to_the_requested_size() {
+        assert_eq!(size_of(&scale(&pixbuf(400, 200), (100, 100), ImageFit::Crop)), (100, 100));
+        assert_eq!(size_of(&scale(&pixbuf(300, 200), (100, 100), ImageFit::Crop)), (100, 100));
+        assert_eq!(size_of(&scale(&pixbuf(333, 100), (90, 50), ImageFit::Crop)), (90, 50));
+    }
+
+    #[test]
+    fn unconstrained_sides_keep_the_aspect_ratio() {
+        for &fit in &[ImageFit::Fit, ImageFit::Crop] {
+            assert_eq!(size_of(&scale(&pixbuf(400, 200), (100, -1), fit)), (100, 50));
+            assert_eq!(size_of(&scale(&pixbuf(400, 200), (0, 100), fit)), (200, 100));
+            assert_eq!(size_of(&scale(&pixbuf(400, 200), (-1, -1), fit)), (400, 200));
+        }
+    }
+
+    #[test]
+    fn scaled_sides_are_at_least_one_pixel() {
+        assert_eq!(size_of(&scale(&pixbuf(1000, 1), (10, 10), ImageFit::Fit)), (10, 1));
+        assert_eq!(size_of(&scale(&pixbuf(1, 1000), (10, 10), ImageFit::Fit)), (1, 10));
+    }
+
+    #[test]
+    fn crop_never_exceeds_the_image() {
+        assert_eq!(size_of(&crop(pixbuf(3, 3), 10, 10)), (3, 3));
+        assert_eq!(size_of(&crop(pixbuf(11, 4), 4, 4)), (4, 4));
+    }
+
+    #[test]
+    fn icons_fit_within_the_requested_size() {
+        assert_eq!(icon_size(32, 64), 32);
+        assert_eq!(icon_size(64, -1), 64);
+        assert_eq!(icon_size(-1, 24), 24);
+        assert_eq!(icon_size(-1, -1), DEFAULT_ICON_SIZE);
+    }
+}
@@ -0,0 +1,1724 @@
+mod import;
+mod loader;
+mod owned;
+mod preview;
+mod section;
+mod settings;
+
+pub use self::owned::{ImageSrcBuf, SelectionVariantBuf, SelectionVariantBuilder};
+
+use self::{
+    import::Importer,
+    loader::{self, ImageSource},
+    preview::PreviewFn,
+    section::Section,
+};
+use super::selection::{self, WeakSelection};
+use gdk_pixbuf::{Pixbuf, PixbufAnimation};
+use gtk::prelude::*;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+/// A list of selections based on radio buttons, with optional images.
+///
+/// The `event_cb` given to the constructor, and any callbacks connected with
+/// `connect_changed`, are invoked exactly once for each change of the active variant.
+/// Selecting the variant which is already active does nothing, and the initial selection
+/// made during construction is not reported.
+///
+/// Selections created with `new_multiple` instead use check buttons, from which any number
+/// of variants may be selected.
+///
+/// Variants may be inserted, removed, updated, and reordered after construction. Variants
+/// are identified by their event, so each variant should have a unique event. Variants may
+/// be given either as borrowed `SelectionVariant`s, or as owned `SelectionVariantBuf`s, which
+/// are built from runtime data with a `SelectionVariantBuilder`.
+///
+/// Variants may be arranged into groups, which are displayed as sections beneath headings in
+/// the order that each group first appears. Every section shares the same selection. The
+/// selection therefore dereferences to the `gtk::Box` which contains its sections, rather
+/// than to a single `gtk::FlowBox`.
+///
+/// Images are decoded and scaled to the variant's `size_request` in a background thread.
+/// The placeholder is displayed until each image has finished loading. Images are loaded at
+/// the scale factor of the display, and are reloaded when the scale factor changes. If an
+/// image fails to load, the placeholder remains in its place, and the failure is reported to
+/// the callbacks connected with `connect_load_error`. Large selections may be virtualized
+/// with `virtualize`, so that the widgets and images of variants only exist while they are
+/// near the visible area.
+///
+/// Animations are paused on their first frame, and only play while their variant is hovered
+/// or selected, and the selection is mapped.
+///
+/// Keyboard navigation follows the behavior of a radio group: focus enters the selection on
+/// the active variant, moving focus with the arrow, Home, and End keys selects the focused
+/// variant, and Space or Enter selects it explicitly. With multiple selection, moving the
+/// focus does not change the selection, and Space or Enter toggles the focused variant.
+/// Moving beyond the first or last variant of a section moves into the adjacent section.
+///
+/// Each variant is exposed to assistive technologies as a radio button named after the
+/// variant, and as a member of the group of variants in the selection; or as a check box
+/// with multiple selection.
+///
+/// Variants are arranged into a configurable number of columns, and images may shrink to fit
+/// narrow windows with `set_adaptive`.
+///
+/// Variants may display a badge over their image, such as "New", and a larger preview of a
+/// variant may be displayed on hover or long-press with `set_preview`.
+///
+/// The widget owns the state of the selection, so the `ImageSelection` may be dropped once
+/// the widget has been added to a container. The selection remains functional until the
+/// widget is destroyed.
+#[derive(AsRef, Deref)]
+pub struct ImageSelection<T: 'static> {
+    #[as_ref]
+    #[deref]
+    container: gtk::Box,
+
+    inner: Rc<Inner<T>>,
+}
+
+impl<T: 'static> Clone for ImageSelection<T> {
+    fn clone(&self) -> Self {
+        Self { container: self.container.clone(), inner: self.inner.clone() }
+    }
+}
+
+impl<T: Clone + 'static> ImageSelection<T> {
+    pub fn new<V: Into<SelectionVariantBuf<T>>>(
+        variants: impl IntoIterator<Item = V>,
+        placeholder: ImageSrc,
+        event_cb: impl Fn(T) + 'static,
+    ) -> Self {
+        let selection = Self::build(variants, placeholder, false);
+        selection.connect_changed(move |_, event| event_cb(event));
+        selection
+    }
+
+    /// Creates a selection of check buttons, from which any number of variants may be
+    /// selected.
+    ///
+    /// The `event_cb` is invoked with the events of every selected variant, in the order of
+    /// the variants, whenever the selection changes.
+    pub fn new_multiple<V: Into<SelectionVariantBuf<T>>>(
+        variants: impl IntoIterator<Item = V>,
+        placeholder: ImageSrc,
+        event_cb: impl Fn(&[T]) + 'static,
+    ) -> Self {
+        let selection = Self::build(variants, placeholder, true);
+        selection.connect_selection_changed(event_cb);
+        selection
+    }
+
+    fn build<V: Into<SelectionVariantBuf<T>>>(
+        variants: impl IntoIterator<Item = V>,
+        placeholder: ImageSrc,
+        multiple: bool,
+    ) -> Self {
+        let container = gtk::Box::new(gtk::Orientation::Vertical, 6);
+
+        let placeholder = ImageSource::from(placeholder)
+            .load()
+            .map_err(|why| error!("failed to load image selection placeholder: {}", why))
+            .ok();
+
+        let group = if multiple { None } else { Some(gtk::RadioButton::new()) };
+
+        let inner = Rc::new(Inner {
+            container:    container.downgrade(),
+            group,
+            sections:     RefCell::new(Vec::new()),
+            variants:     RefCell::new(Vec::new()),
+            placeholder,
+            multiple,
+            fit:          Cell::new(ImageFit::default()),
+            active:       RefCell::new(None),
+            silent:       Cell::new(true),
+            pending:      Cell::new(false),
+            query:        RefCell::new(Vec::new()),
+            changed_cb:   RefCell::new(Vec::new()),
+            selection_cb: RefCell::new(Vec::new()),
+            error_cb:     RefCell::new(Vec::new()),
+            importer:     RefCell::new(None),
+            viewport:     RefCell::new(None),
+            queued:       Cell::new(false),
+            hovered:      RefCell::new(None),
+            preview_cb:   RefCell::new(None),
+            preview:      RefCell::new(None),
+            hover:        RefCell::new(None),
+            columns:      Cell::new((0, 7)),
+            spacing:      Cell::new((0, 0)),
+            adaptive:     RefCell::new(None),
+            shrink:       Cell::new(1.0),
+            syncing:      Cell::new(false),
+            unbuilt:      Cell::new(false),
+            estimate:     Cell::new(None),
+            near:         RefCell::new(HashSet::new()),
+            positions:    RefCell::new(HashMap::new()),
+        });
+
+        let mut active_children = Vec::new();
+
+        for variant in variants {
+            let variant = variant.into();
+            let child = Inner::insert(&inner, None, &variant);
+
+            if variant.active {
+                active_children.push(child);
+            }
+        }
+
+        for child in active_children {
+            Inner::select(&inner, &child, true);
+        }
+
+        inner.silent.set(false);
+        inner.pending.set(false);
+        inner.relate_group();
+
+        // Focus entering a radio group lands on the active variant, unless it is filtered.
+        if !multiple {
+            let inner_ = Rc::downgrade(&inner);
+            selection::enter_on_active(&container, move || {
+                inner_.upgrade().and_then(|inner| inner.active_child())
+            });
+        }
+
+        // Animations only play while the selection is mapped.
+        let inner_ = Rc::downgrade(&inner);
+        container.connect_map(move |_| {
+            if let Some(inner) = inner_.upgrade() {
+                Inner::queue_update(&inner);
+            }
+        });
+
+        let inner_ = Rc::downgrade(&inner);
+        container.connect_unmap(move |_| {
+            if let Some(inner) = inner_.upgrade() {
+                Inner::queue_update(&inner);
+            }
+        });
+
+        let inner_ = Rc::downgrade(&inner);
+        container.connect_scale_factor_notify(move |_| {
+            if let Some(inner) = inner_.upgrade() {
+                Inner::reload_images(&inner);
+            }
+        });
+
+        // The widget owns the state of the selection, which is released once it is destroyed
+        // after cancelling pending image loads.
+        let inner_ = RefCell::new(Some(inner.clone()));
+        container.connect_destroy(move |_| {
+            if let Some(inner) = inner_.borrow_mut().take() {
+                inner.hide_preview();
+
+                for variant in inner.variants.borrow_mut().drain(..) {
+                    variant.cancel_load();
+                }
+
+                inner.sections.borrow_mut().clear();
+            }
+        });
+
+        Self { container, inner }
+    }
+
+    /// The event of the currently-active variant, if there are any variants.
+    ///
+    /// This is always `None` with multiple selection.
+    pub fn active(&self) -> Option<T> { self.inner.active.borrow().clone() }
+
+    /// Activates the variant associated with `event`, invoking the change callbacks if the
+    /// selection changed.
+    ///
+    /// With multiple selection, the variant is added to the selection. Returns `false` if no
+    /// variant is associated with the given event.
+    pub fn set_active(&self, event: T) -> bool
+    where
+        T: PartialEq,
+    {
+        match self.inner.position_of(&event) {
+            Some(position) => {
+                Inner::select_at(&self.inner, position, true);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Activates the variant associated with `event` without invoking any callbacks.
+    ///
+    /// Use this when synchronizing the widget with external state that has already changed.
+    pub fn set_active_silent(&self, event: T) -> bool
+    where
+        T: PartialEq,
+    {
+        let silent = self.inner.silent.replace(true);
+        let found = self.set_active(event);
+        self.inner.silent.set(silent);
+        found
+    }
+
+    /// Invoked with the previous and the new event whenever the active variant changes.
+    ///
+    /// This is never invoked with multiple selection.
+    pub fn connect_changed<F: Fn(Option<T>, T) + 'static>(&self, func: F) {
+        self.inner.changed_cb.borrow_mut().push(Box::new(func));
+    }
+
+    /// Invoked with the events of every selected variant whenever the selection changes.
+    pub fn connect_selection_changed<F: Fn(&[T]) + 'static>(&self, func: F) {
+        self.inner.selection_cb.borrow_mut().push(Box::new(func));
+    }
+
+    /// The events of every selected variant, in the order of the variants.
+    pub fn selected(&self) -> Vec<T> { self.inner.selected() }
+
+    /// Selects exactly the variants associated with the given events.
+    ///
+    /// The change is reported once, rather than for each variant. Has no effect unless the
+    /// selection was created with `new_multiple`.
+    pub fn set_selected(&self, events: &[T])
+    where
+        T: PartialEq,
+    {
+        Inner::batch(&self.inner, |variant| events.contains(&variant.event));
+    }
+
+    /// Selects exactly the variants associated with the given events, without invoking any
+    /// callbacks.
+    ///
+    /// Use this when synchronizing the widget with external state that has already changed.
+    pub fn set_selected_silent(&self, events: &[T])
+    where
+        T: PartialEq,
+    {
+        let silent = self.inner.silent.replace(true);
+        self.set_selected(events);
+        self.inner.silent.set(silent);
+        self.inner.pending.set(false);
+    }
+
+    /// Selects every variant.
+    ///
+    /// The change is reported once, rather than for each variant. Has no effect unless the
+    /// selection was created with `new_multiple`.
+    pub fn select_all(&self) { Inner::batch(&self.inner, |_| true); }
+
+    /// Deselects every variant.
+    ///
+    /// The change is reported once, rather than for each variant. Has no effect unless the
+    /// selection was created with `new_multiple`.
+    pub fn select_none(&self) { Inner::batch(&self.inner, |_| false); }
+
+    /// Invoked with the event of a variant whose image failed to load, and the reason why.
+    ///
+    /// The callback is invoked from the main loop, so it may safely modify the selection;
+    /// such as by removing the variant. Failures are logged if no callback is connected.
+    pub fn connect_load_error<F: Fn(T, &glib::Error) + 'static>(&self, func: F) {
+        self.inner.error_cb.borrow_mut().push(Box::new(func));
+    }
+
+    /// How images are scaled to the size requested by their variants.
+    pub fn image_fit(&self) -> ImageFit { self.inner.fit.get() }
+
+    /// Defines how images are scaled to the size requested by their variants.
+    ///
+    /// Images which have already been loaded will be reloaded.
+    pub fn set_image_fit(&self, fit: ImageFit) {
+        if self.inner.fit.replace(fit) != fit {
+            Inner::reload_images(&self.inner);
+        }
+    }
+
+    /// Limits the number of columns which the variants of each section are arranged in.
+    ///
+    /// By default, a section has between zero and seven columns.
+    pub fn set_columns(&self, min: u32, max: u32) {
+        self.inner.columns.set((min, max.max(min)));
+        self.inner.arrange();
+        Inner::queue_update(&self.inner);
+    }
+
+    /// Spaces the rows and the columns of variants within each section.
+    pub fn set_spacing(&self, row: u32, column: u32) {
+        self.inner.spacing.set((row, column));
+        self.inner.arrange();
+        Inner::queue_update(&self.inner);
+    }
+
+    /// Disables the horizontal scrollbar of a scrolled window which contains the selection,
+    /// and shrinks the images of the variants so that the minimum number of columns fits
+    /// within its width.
+    ///
+    /// The horizontal scrollbar policy of the scrolled window is set to `External`, so that
+    /// the width of the scrolled window is not bound to the requested size of the images. The
+    /// previous policy is restored when another scrolled window is made adaptive.
+    ///
+    /// Images are shrunk to no less than `min_scale` of their requested size, and are never
+    /// enlarged.
+    pub fn set_adaptive(&self, scrolled_window: &gtk::ScrolledWindow, min_scale: f64) {
+        let previous = self.inner.adaptive.borrow_mut().take();
+        if let Some(previous) = previous {
+            if let Some(scrolled_window) = previous.scrolled_window.upgrade() {
+                scrolled_window.disconnect(previous.resized);
+                scrolled_window.set_hscrollbar_policy(previous.policy);
+            }
+        }
+
+        let policy = scrolled_window.hscrollbar_policy();
+        scrolled_window.set_hscrollbar_policy(gtk::PolicyType::External);
+
+        let inner = Rc::downgrade(&self.inner);
+        let resized = scrolled_window.connect_size_allocate(move |_, _| {
+            if let Some(inner) = inner.upgrade() {
+                Inner::queue_update(&inner);
+            }
+        });
+
+        *self.inner.adaptive.borrow_mut() = Some(Adaptive {
+            scrolled_window: scrolled_window.downgrade(),
+            min_shrink: min_scale.max(0.0).min(1.0),
+            resized,
+            policy,
+        });
+
+        Inner::queue_update(&self.inner);
+    }
+
+    /// The number of variants in the selection.
+    pub fn len(&self) -> usize { self.inner.variants.borrow().len() }
+
+    /// Whether the selection contains no variants.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Appends a variant to the end of the selection.
+    pub fn push(&self, variant: impl Into<SelectionVariantBuf<T>>) {
+        self.insert(usize::MAX, variant);
+    }
+
+    /// Inserts a variant at the given position, or at the end if the position is out of
+    /// bounds.
+    ///
+    /// If the variant is marked as active, it will become the active variant.
+    pub fn insert(&self, position: usize, variant: impl Into<SelectionVariantBuf<T>>) {
+        let variant = variant.into();
+        let child = Inner::insert(&self.inner, Some(position), &variant);
+        self.inner.relate_group();
+
+        if variant.active {
+            Inner::select(&self.inner, &child, true);
+        }
+    }
+
+    /// Removes the variant associated with `event` from the selection.
+    ///
+    /// If the removed variant was active in a radio group, the first remaining variant
+    /// becomes active. Returns `false` if no variant is associated with the given event.
+    pub fn remove(&self, event: T) -> bool
+    where
+        T: PartialEq,
+    {
+        let removed = {
+            let mut variants = self.inner.variants.borrow_mut();
+            match variants.iter().position(|variant| variant.event == event) {
+                Some(position) => variants.remove(position),
+                None => return false,
+            }
+        };
+
+        self.inner.positions.borrow_mut().clear();
+        removed.cancel_load();
+        unsafe { removed.child.destroy() };
+        self.inner.relate_group();
+        self.inner.refresh_sections();
+
+        if removed.selected {
+            if self.inner.multiple {
+                self.inner.toggled();
+            } else if self.inner.variants.borrow().is_empty() {
+                *self.inner.active.borrow_mut() = None;
+            } else {
+                Inner::select_at(&self.inner, 0, true);
+            }
+        }
+
+        true
+    }
+
+    /// Updates the name, keywords, group, subtitle, badge, tooltip, sensitivity, description,
+    /// image, and size request of the variant associated with `event`.
+    ///
+    /// The variant will also take the event of the new variant, and will be activated if
+    /// the new variant is marked as active. Returns `false` if no variant is associated with
+    /// the given event.
+    pub fn update(&self, event: T, variant: impl Into<SelectionVariantBuf<T>>) -> bool
+    where
+        T: PartialEq,
+    {
+        let variant = variant.into();
+        let (position, selected) = {
+            let mut variants = self.inner.variants.borrow_mut();
+            let position = match variants.iter().position(|current| current.event == event) {
+                Some(position) => position,
+                None => return false,
+            };
+
+            let current = &mut variants[position];
+            current.event = variant.event.clone();
+            current.present(&variant);
+            self.inner.describe(&current.child, &variant);
+            current.source = variant.image.as_ref().map(ImageSource::from);
+            current.size = variant.size_request;
+            Inner::load(&self.inner, current);
+            (position, current.selected)
+        };
+
+        Inner::regroup(&self.inner, variant.event.clone(), variant.group.as_deref());
+
+        if selected && !self.inner.multiple {
+            *self.inner.active.borrow_mut() = Some(variant.event);
+        } else if variant.active {
+            Inner::select_at(&self.inner, position, true);
+        }
+
+        true
+    }
+
+    /// Moves the variant associated with `event` to the given position, or to the end if the
+    /// position is out of bounds.
+    ///
+    /// Returns `false` if no variant is associated with the given event.
+    pub fn reorder(&self, event: T, position: usize) -> bool
+    where
+        T: PartialEq,
+    {
+        let (child, flow_box, position) = {
+            let mut variants = self.inner.variants.borrow_mut();
+            let current = match variants.iter().position(|variant| variant.event == event) {
+                Some(current) => current,
+                None => return false,
+            };
+
+            let variant = variants.remove(current);
+            let position = position.min(variants.len());
+            let (child, flow_box) = (variant.child.clone(), variant.flow_box.clone());
+            variants.insert(position, variant);
+            let position = position_in(&variants, &flow_box, position);
+            (child, flow_box, position)
+        };
+
+        self.inner.positions.borrow_mut().clear();
+
+        flow_box.remove(&child);
+        flow_box.insert(&child, position as i32);
+
+        true
+    }
+
+    /// Displays only the variants whose name or keywords contain every word of the query.
+    ///
+    /// Matching ignores case, and an empty query displays every variant. Sections without
+    /// matching variants are hidden along with their headings. The selection is unaffected,
+    /// even if the active variant is hidden.
+    pub fn set_filter(&self, query: &str) {
+        *self.inner.query.borrow_mut() =
+            query.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>();
+
+        for section in self.inner.sections.borrow().iter() {
+            section.flow_box.invalidate_filter();
+        }
+
+        self.inner.refresh_sections();
+
+        Inner::queue_update(&self.inner);
+    }
+
+    /// Creates the widgets and loads the images of only the variants near the visible area of
+    /// a scrolled window which contains the selection, and destroys them for the variants
+    /// which scroll away.
+    ///
+    /// Variants within a page of the visible area are created ahead of time. Every other
+    /// variant is an empty child, which requests the size of the first variant so that the
+    /// layout remains stable while scrolling. This bounds the widgets and the decoded images
+    /// held by the selection, so that catalogs of thousands of images may be browsed.
+    pub fn virtualize(&self, scrolled_window: &gtk::ScrolledWindow) {
+        *self.inner.viewport.borrow_mut() = Some(scrolled_window.downgrade());
+
+        for adjustment in &[scrolled_window.hadjustment(), scrolled_window.vadjustment()] {
+            let inner = Rc::downgrade(&self.inner);
+            adjustment.connect_value_changed(move |_| {
+                if let Some(inner) = inner.upgrade() {
+                    Inner::queue_update(&inner);
+                }
+            });
+        }
+
+        let inner = Rc::downgrade(&self.inner);
+        scrolled_window.connect_size_allocate(move |_, _| {
+            if let Some(inner) = inner.upgrade() {
+                Inner::queue_update(&inner);
+            }
+        });
+
+        let inner = Rc::downgrade(&self.inner);
+        self.container.connect_size_allocate(move |_, _| {
+            if let Some(inner) = inner.upgrade() {
+                Inner::queue_update(&inner);
+            }
+        });
+
+        // Widgets which were created beforehand are recreated once they are near the viewport.
+        for variant in self.inner.variants.borrow_mut().iter_mut() {
+            variant.clear();
+        }
+
+        self.inner.near.borrow_mut().clear();
+        Inner::reload_images(&self.inner);
+    }
+
+    /// Filters the variants by the text of a search entry whenever its search changes.
+    pub fn bind_search_entry(&self, entry: &gtk::SearchEntry) {
+        let selection = self.downgrade();
+        entry.connect_search_changed(move |entry| {
+            if let Some(selection) = selection.upgrade().map(Self::from_parts) {
+                selection.set_filter(&entry.text());
+            }
+        });
+
+        entry.connect_stop_search(|entry| entry.set_text(""));
+    }
+
+    /// Creates a search entry which filters the variants as the search changes.
+    ///
+    /// The entry is not packed, so it may be placed anywhere in the interface.
+    pub fn search_entry(&self) -> gtk::SearchEntry {
+        let entry = gtk::SearchEntry::new();
+        self.bind_search_entry(&entry);
+        entry
+    }
+}
+
+impl<T: 'static> ImageSelection<T> {
+    /// A reference to the selection which does not keep it alive.
+    fn downgrade(&self) -> WeakSelection<gtk::Box, Inner<T>> {
+        WeakSelection::new(&self.container, &self.inner)
+    }
+
+    fn from_parts((container, inner): (gtk::Box, Rc<Inner<T>>)) -> Self {
+        Self { container, inner }
+    }
+}
+
+struct Inner<T> {
+    container:    glib::WeakRef<gtk::Box>,
+    /// The hidden leader of the radio group, which is active while the selected variant has
+    /// no widgets.
+    group:        Option<gtk::RadioButton>,
+    sections:     RefCell<Vec<Section>>,
+    variants:     RefCell<Vec<Variant<T>>>,
+    placeholder:  Option<Pixbuf>,
+    multiple:     bool,
+    fit:          Cell<ImageFit>,
+    active:       RefCell<Option<T>>,
+    silent:       Cell<bool>,
+    pending:      Cell<bool>,
+    query:        RefCell<Vec<String>>,
+    changed_cb:   RefCell<Vec<Box<dyn Fn(Option<T>, T)>>>,
+    selection_cb: RefCell<Vec<Box<dyn Fn(&[T])>>>,
+    error_cb:     RefCell<Vec<Box<dyn Fn(T, &glib::Error)>>>,
+    importer:     RefCell<Option<Importer<T>>>,
+    viewport:     RefCell<Option<glib::WeakRef<gtk::ScrolledWindow>>>,
+    queued:       Cell<bool>,
+    hovered:      RefCell<Option<gtk::FlowBoxChild>>,
+    preview_cb:   RefCell<Option<PreviewFn<T>>>,
+    preview:      RefCell<Option<gtk::Popover>>,
+    hover:        RefCell<Option<glib::SourceId>>,
+    columns:      Cell<(u32, u32)>,
+    spacing:      Cell<(u32, u32)>,
+    adaptive:     RefCell<Option<Adaptive>>,
+    shrink:       Cell<f64>,
+    /// Whether the buttons are being synchronized with the selection.
+    syncing:      Cell<bool>,
+    /// Whether variants have been inserted without widgets since the last update.
+    unbuilt:      Cell<bool>,
+    /// The size requested by the children of variants without widgets, when virtualized.
+    estimate:     Cell<Option<(i32, i32)>>,
+    /// The children of the variants near the viewport, when virtualized.
+    near:         RefCell<HashSet<gtk::FlowBoxChild>>,
+    /// The position of the variant of each child, which is cleared when variants are moved.
+    positions:    RefCell<HashMap<gtk::FlowBoxChild, usize>>,
+}
+
+impl<T: Clone + 'static> Inner<T> {
+    /// Creates the child of a variant, and inserts it into the container.
+    ///
+    /// The widgets within the child are created when the selection is next updated, or once
+    /// the child is near the viewport when virtualized.
+    fn insert(
+        inner: &Rc<Self>,
+        position: Option<usize>,
+        variant: &SelectionVariantBuf<T>,
+    ) -> gtk::FlowBoxChild {
+        let child = cascade! {
+            gtk::FlowBoxChild::new();
+            ..set_can_focus(true);
+            ..show();
+        };
+
+        if let Some((width, height)) = inner.estimate.get() {
+            child.set_size_request(width, height);
+        }
+
+        // Selection follows the keyboard focus in a radio group.
+        if !inner.multiple {
+            let inner_ = Rc::downgrade(inner);
+            let child_ = child.downgrade();
+            selection::select_on_focus(&child, move || {
+                if let (Some(inner), Some(child)) = (inner_.upgrade(), child_.upgrade()) {
+                    Inner::select(&inner, &child, true);
+                }
+            });
+        }
+
+        inner.describe(&child, variant);
+
+        let flow_box = Inner::section(inner, variant.group.as_deref());
+
+        // Until a variant is marked as active, the first variant of a radio group is selected.
+        let selected = !inner.multiple && inner.active.borrow().is_none();
+        if selected {
+            *inner.active.borrow_mut() = Some(variant.event.clone());
+        }
+
+        let mut new_variant = Variant {
+            child:    child.clone(),
+            flow_box: flow_box.clone(),
+            content:  None,
+            name:     String::new(),
+            subtitle: None,
+            badge:    None,
+            terms:    Vec::new(),
+            source:   variant.image.as_ref().map(ImageSource::from),
+            size:     variant.size_request,
+            loading:  None,
+            loaded:   false,
+            playing:  None,
+            selected,
+            event:    variant.event.clone(),
+        };
+
+        new_variant.present(variant);
+
+        // The variant must be recorded before the container filters the child.
+        let position = {
+            let mut variants = inner.variants.borrow_mut();
+            let position =
+                position.map_or(variants.len(), |position| position.min(variants.len()));
+            variants.insert(position, new_variant);
+            position_in(&variants, &flow_box, position)
+        };
+
+        inner.positions.borrow_mut().clear();
+        flow_box.insert(&child, position as i32);
+
+        if inner.matches(&child) {
+            if let Some(section) = inner.sections.borrow().iter().find(|s| s.flow_box == flow_box) {
+                section.set_visible(true);
+            }
+        }
+
+        inner.unbuilt.set(true);
+        Inner::queue_update(inner);
+
+        child
+    }
+
+    /// Creates the widgets within the child of a variant, and begins loading its image.
+    fn populate(inner: &Rc<Self>, variant: &mut Variant<T>) {
+        if variant.content.is_some() {
+            return;
+        }
+
+        let button = if inner.multiple {
+            gtk::CheckButton::new()
+        } else {
+            let radio = cascade! {
+                gtk::RadioButton::new();
+                ..join_group(inner.group.as_ref());
+            };
+
+            radio.upcast::<gtk::CheckButton>()
+        };
+
+        button.set_can_focus(false);
+        button.set_halign(gtk::Align::Center);
+
+        let image = gtk::Image::new();
+
+        let badge = gtk::LabelBuilder::new()
+            .halign(gtk::Align::End)
+            .valign(gtk::Align::Start)
+            .margin(4)
+            .no_show_all(true)
+            .build();
+
+        badge.style_context().add_class("badge");
+        badge.style_context().add_class(&gtk::STYLE_CLASS_OSD);
+
+        let label = gtk::LabelBuilder::new().xalign(0.0).halign(gtk::Align::Center).build();
+
+        let subtitle = gtk::LabelBuilder::new()
+            .xalign(0.0)
+            .halign(gtk::Align::Center)
+            .no_show_all(true)
+            .build();
+
+        subtitle.style_context().add_class(&gtk::STYLE_CLASS_DIM_LABEL);
+
+        let widget = cascade! {
+            gtk::Box::new(gtk::Orientation::Vertical, 12);
+            ..add(&cascade! {
+                gtk::Overlay::new();
+                ..add(&image);
+                ..add_overlay(&badge);
+            });
+            ..add(&cascade! {
+                gtk::Box::new(gtk::Orientation::Vertical, 2);
+                ..add(&label);
+                ..add(&subtitle);
+            });
+            ..add(&button);
+        };
+
+        let event_box = cascade! {
+            gtk::EventBox::new();
+            ..add(&widget);
+            ..show_all();
+        };
+
+        variant.child.add(&event_box);
+
+        let gesture = Inner::watch_hover(inner, &variant.child, &event_box);
+
+        variant.content = Some(Content {
+            event_box,
+            button:   button.clone(),
+            image,
+            badge,
+            label,
+            subtitle,
+            _gesture: gesture,
+        });
+
+        variant.display();
+        inner.sync_button(variant);
+
+        let inner_ = Rc::downgrade(inner);
+        let child = variant.child.downgrade();
+        button.connect_active_notify(move |button| {
+            if let (Some(inner), Some(child)) = (inner_.upgrade(), child.upgrade()) {
+                if !inner.syncing.get() {
+                    Inner::select(&inner, &child, button.is_active());
+                }
+            }
+        });
+
+        Inner::fetch(inner, variant);
+    }
+
+    /// The flow box of the section for the given group, which is created if it is missing.
+    fn section(inner: &Rc<Self>, group: Option<&str>) -> gtk::FlowBox {
+        let existing = inner
+            .sections
+            .borrow()
+            .iter()
+            .find(|section| section.name.as_deref() == group)
+            .map(|section| section.flow_box.clone());
+
+        if let Some(flow_box) = existing {
+            return flow_box;
+        }
+
+        let section = Section::new(group);
+        section.arrange(inner.columns.get(), inner.spacing.get());
+        let flow_box = section.flow_box.clone();
+
+        if let Some(container) = inner.container.upgrade() {
+            if let Some(heading) = section.heading.as_ref() {
+                container.add(heading);
+            }
+
+            container.add(&flow_box);
+        }
+
+        let inner_ = Rc::downgrade(inner);
+        flow_box.connect_child_activated(move |_, child| {
+            let inner = match inner_.upgrade() {
+                Some(inner) => inner,
+                None => return,
+            };
+
+            let variant = inner
+                .variants
+                .borrow()
+                .iter()
+                .enumerate()
+                .find(|(_, variant)| &variant.child == child)
+                .map(|(position, variant)| (position, variant.selected));
+
+            if let Some((position, selected)) = variant {
+                Inner::select_at(&inner, position, !inner.multiple || !selected);
+            }
+        });
+
+        let inner_ = Rc::downgrade(inner);
+        flow_box.set_filter_func(Some(Box::new(move |child| {
+            inner_.upgrade().map_or(true, |inner| inner.matches(child))
+        })));
+
+        let inner_ = Rc::downgrade(inner);
+        flow_box.connect_keynav_failed(move |flow_box, direction| {
+            gtk::Inhibit(inner_.upgrade().map_or(false, |inner| inner.cross(flow_box, direction)))
+        });
+
+        inner.sections.borrow_mut().push(section);
+
+        flow_box
+    }
+
+    /// Moves a variant into the section of another group, if its group has changed.
+    fn regroup(inner: &Rc<Self>, event: T, group: Option<&str>)
+    where
+        T: PartialEq,
+    {
+        let flow_box = Inner::section(inner, group);
+
+        let moved = {
+            let mut variants = inner.variants.borrow_mut();
+            let current = match variants.iter().position(|variant| variant.event == event) {
+                Some(current) => current,
+                None => return,
+            };
+
+            let previous = std::mem::replace(&mut variants[current].flow_box, flow_box.clone());
+            if previous == flow_box {
+                None
+            } else {
+                let child = variants[current].child.clone();
+                Some((child, previous, position_in(&variants, &flow_box, current)))
+            }
+        };
+
+        match moved {
+            Some((child, previous, position)) => {
+                previous.remove(&child);
+                flow_box.insert(&child, position as i32);
+            }
+            None => flow_box.invalidate_filter(),
+        }
+
+        inner.refresh_sections();
+    }
+
+    /// Applies the column limits and the spacing to every section.
+    fn arrange(&self) {
+        for section in self.sections.borrow().iter() {
+            section.arrange(self.columns.get(), self.spacing.get());
+        }
+    }
+
+    /// Displays only the sections which contain variants matching the filter query, or the
+    /// import tile.
+    fn refresh_sections(&self) {
+        let (variants, query) = (self.variants.borrow(), self.query.borrow());
+        for section in self.sections.borrow().iter() {
+            section.set_visible(
+                section.tile.is_some()
+                    || variants.iter().any(|v| v.flow_box == section.flow_box && v.matches(&query)),
+            );
+        }
+    }
+
+    /// Moves the focus from the edge of a section into the adjacent section.
+    ///
+    /// Returns `false` if there is no section in that direction to move into.
+    fn cross(&self, flow_box: &gtk::FlowBox, direction: gtk::DirectionType) -> bool {
+        let target = {
+            let sections = self.sections.borrow();
+            let index = match sections.iter().position(|s| &s.flow_box == flow_box) {
+                Some(index) => index,
+                None => return false,
+            };
+
+            match direction {
+                gtk::DirectionType::Down | gtk::DirectionType::Right => {
+                    sections[index + 1..].iter().find_map(Section::first_child)
+                }
+                gtk::DirectionType::Up | gtk::DirectionType::Left => {
+                    sections[..index].iter().rev().find_map(Section::last_child)
+                }
+                _ => None,
+            }
+        };
+
+        match target {
+            Some(child) => {
+                child.grab_focus();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Begins loading the image of a variant, reporting any failure to the error callbacks.
+    ///
+    /// When virtualized, the image is instead loaded once the variant is near the viewport.
+    fn load(inner: &Rc<Self>, variant: &mut Variant<T>) {
+        if inner.viewport.borrow().is_some() {
+            variant.release(inner.placeholder.as_ref(), inner.shrink.get());
+        } else {
+            Inner::fetch(inner, variant);
+        }
+
+        Inner::queue_update(inner);
+    }
+
+    /// Begins loading the image of a variant immediately.
+    fn fetch(inner: &Rc<Self>, variant: &mut Variant<T>) {
+        let event = variant.event.clone();
+        let inner_ = Rc::downgrade(inner);
+        let (fit, shrink) = (inner.fit.get(), inner.shrink.get());
+        variant.load(inner.placeholder.as_ref(), fit, shrink, move |why| {
+            if let Some(inner) = inner_.upgrade() {
+                inner.load_failed(event, why);
+            }
+        });
+    }
+
+    /// Updates the images of the variants from the main loop, once the layout and the
+    /// selection have settled.
+    fn queue_update(inner: &Rc<Self>) {
+        if inner.queued.replace(true) {
+            return;
+        }
+
+        let inner = Rc::downgrade(inner);
+        glib::idle_add_local(move || {
+            if let Some(inner) = inner.upgrade() {
+                inner.queued.set(false);
+                Inner::adapt(&inner);
+                Inner::populate_all(&inner);
+                Inner::load_visible(&inner);
+                Inner::animate(&inner);
+            }
+
+            glib::Continue(false)
+        });
+    }
+
+    /// Creates the widgets of the variants which were inserted since the last update, unless
+    /// the selection is virtualized.
+    fn populate_all(inner: &Rc<Self>) {
+        if !inner.unbuilt.replace(false) || inner.viewport.borrow().is_some() {
+            return;
+        }
+
+        for variant in inner.variants.borrow_mut().iter_mut() {
+            Inner::populate(inner, variant);
+        }
+    }
+
+    /// Creates the widgets and loads the images of the variants within a page of the visible
+    /// area of the viewport, and destroys the widgets of the variants which scrolled away.
+    fn load_visible(inner: &Rc<Self>) {
+        let viewport = match inner.viewport.borrow().as_ref().and_then(glib::WeakRef::upgrade) {
+            Some(viewport) => viewport,
+            None => return,
+        };
+
+        let height = viewport.allocated_height();
+        if height <= 1 {
+            return;
+        }
+
+        // The layout must reflect the estimate before the near variants are found.
+        if inner.estimate.get().is_none() {
+            Inner::estimate(inner);
+            return;
+        }
+
+        let near = inner
+            .sections
+            .borrow()
+            .iter()
+            .flat_map(|section| section.near(&viewport, height))
+            .collect::<HashSet<_>>();
+
+        let previous = inner.near.replace(near.clone());
+
+        inner.index();
+        let positions = inner.positions.borrow();
+        let mut variants = inner.variants.borrow_mut();
+
+        for child in previous.difference(&near) {
+            if let Some(&position) = positions.get(child) {
+                variants[position].clear();
+            }
+        }
+
+        for child in &near {
+            if let Some(&position) = positions.get(child) {
+                let variant = &mut variants[position];
+                if variant.content.is_none() {
+                    Inner::populate(inner, variant);
+                } else if !variant.loaded {
+                    Inner::fetch(inner, variant);
+                }
+            }
+        }
+    }
+
+    /// Measures the first variant, whose size is then requested by the children of variants
+    /// without widgets, so that the layout is stable as widgets are created and destroyed.
+    fn estimate(inner: &Rc<Self>) {
+        let mut variants = inner.variants.borrow_mut();
+        let first = match variants.first_mut() {
+            Some(first) => first,
+            None => return,
+        };
+
+        Inner::populate(inner, first);
+        first.child.set_size_request(-1, -1);
+        inner.near.borrow_mut().insert(first.child.clone());
+
+        let (_, natural) = first.child.preferred_size();
+        inner.estimate.set(Some((natural.width, natural.height)));
+
+        for variant in variants.iter() {
+            variant.child.set_size_request(natural.width, natural.height);
+        }
+    }
+
+    /// Shrinks the images so that the minimum number of columns fits within the width of the
+    /// adaptive scrolled window, reloading the images if their size has changed.
+    fn adapt(inner: &Rc<Self>) {
+        let (scrolled_window, min_shrink) = match inner.adaptive.borrow().as_ref() {
+            Some(adaptive) => (adaptive.scrolled_window.upgrade(), adaptive.min_shrink),
+            None => return,
+        };
+
+        let width = match scrolled_window {
+            Some(scrolled_window) => f64::from(scrolled_window.allocated_width()),
+            None => return,
+        };
+
+        let item_width = inner
+            .variants
+            .borrow()
+            .iter()
+            .filter_map(|variant| variant.size.map(|(width, _)| width))
+            .max()
+            .unwrap_or(0);
+
+        if item_width < 1 || width < 1.0 {
+            return;
+        }
+
+        let columns = f64::from(inner.columns.get().0.max(1));
+        let spacing = f64::from(inner.spacing.get().1) * (columns - 1.0);
+        let fit = (width - spacing) / (columns * f64::from(item_width));
+
+        // Steps of a sixteenth avoid reloading every image for each pixel of a resize.
+        let shrink = ((fit * 16.0).floor() / 16.0).max(min_shrink).min(1.0);
+
+        if (inner.shrink.replace(shrink) - shrink).abs() > f64::EPSILON {
+            Inner::reload_images(inner);
+        }
+    }
+
+    /// Plays the animations of the hovered and selected variants while the selection is
+    /// mapped, and pauses every other animation on its first frame.
+    fn animate(inner: &Rc<Self>) {
+        let mapped = inner.container.upgrade().map_or(false, |container| container.is_mapped());
+        let hovered = inner.hovered.borrow().clone();
+
+        for variant in inner.variants.borrow_mut().iter_mut() {
+            let animation = match variant.source.as_ref() {
+                Some(ImageSource::Animation(animation)) => animation.clone(),
+                _ => continue,
+            };
+
+            let play = mapped
+                && variant.loaded
+                && (variant.selected || hovered.as_ref() == Some(&variant.child));
+
+            if play && variant.playing.is_none() {
+                variant.play(&animation, inner.fit.get(), inner.shrink.get());
+            } else if !play && variant.playing.is_some() {
+                Inner::fetch(inner, variant);
+            }
+        }
+    }
+
+    /// Reports an image which failed to load from the main loop, so that the callbacks are
+    /// free to modify the variants.
+    fn load_failed(self: Rc<Self>, event: T, why: glib::Error) {
+        let mut failure = Some((self, event, why));
+        glib::idle_add_local(move || {
+            if let Some((inner, event, why)) = failure.take() {
+                let callbacks = inner.error_cb.borrow();
+                if callbacks.is_empty() {
+                    error!("failed to load image for selection variant: {}", why);
+                }
+
+                for func in callbacks.iter() {
+                    func(event.clone(), &why);
+                }
+            }
+
+            glib::Continue(false)
+        });
+    }
+
+    /// Reloads the images of every variant, and measures the size of the variants again.
+    fn reload_images(inner: &Rc<Self>) {
+        inner.estimate.set(None);
+
+        for variant in inner.variants.borrow_mut().iter_mut() {
+            Inner::load(inner, variant);
+        }
+    }
+
+    /// Records the newly-active variant, and notifies the callbacks of the change.
+    fn activated(&self, event: T) {
+        let previous = self.active.replace(Some(event.clone()));
+
+        if self.silent.get() {
+            return;
+        }
+
+        for func in self.changed_cb.borrow().iter() {
+            func(previous.clone(), event.clone());
+        }
+
+        for func in self.selection_cb.borrow().iter() {
+            func(std::slice::from_ref(&event));
+        }
+    }
+
+    /// Notifies the callbacks of a change to the multiple selection, or defers the
+    /// notification until the end of a batch of changes.
+    fn toggled(&self) {
+        if self.silent.get() {
+            self.pending.set(true);
+            return;
+        }
+
+        let selected = self.selected();
+        for func in self.selection_cb.borrow().iter() {
+            func(&selected);
+        }
+    }
+
+    /// Selects or deselects the variant of the child, and notifies the callbacks of the change.
+    fn select(inner: &Rc<Self>, child: &gtk::FlowBoxChild, selected: bool) {
+        let position = inner.variants.borrow().iter().position(|variant| &variant.child == child);
+        if let Some(position) = position {
+            Inner::select_at(inner, position, selected);
+        }
+    }
+
+    /// Selects or deselects the variant at the position, and notifies the callbacks of the
+    /// change.
+    ///
+    /// A variant of a radio group is only deselected by selecting another variant.
+    fn select_at(inner: &Rc<Self>, position: usize, selected: bool) {
+        let (event, child, changed) = {
+            let mut variants = inner.variants.borrow_mut();
+            let current = &variants[position];
+            if current.selected == selected || !(selected || inner.multiple) {
+                return;
+            }
+
+            let mut changed = vec![(current.child.clone(), selected)];
+            if !inner.multiple {
+                for variant in variants.iter_mut().filter(|variant| variant.selected) {
+                    variant.selected = false;
+                    changed.push((variant.child.clone(), false));
+                }
+            }
+
+            let current = &mut variants[position];
+            current.selected = selected;
+            inner.sync_button(current);
+            (current.event.clone(), current.child.clone(), changed)
+        };
+
+        for (child, selected) in changed {
+            if let Some(accessible) = child.accessible() {
+                accessible.notify_state_change(atk::State::Checked, selected);
+            }
+        }
+
+        Inner::queue_update(inner);
+
+        if inner.multiple {
+            inner.toggled();
+        } else {
+            inner.activated(event);
+            selection::follow_focus(&inner.container, &child);
+        }
+    }
+
+    /// Displays whether the variant is selected on its button, if it has widgets.
+    ///
+    /// The hidden leader of a radio group is activated in place of a selected variant without
+    /// widgets, so that no other button remains active.
+    fn sync_button(&self, variant: &Variant<T>) {
+        let syncing = self.syncing.replace(true);
+
+        match (variant.content.as_ref(), self.group.as_ref()) {
+            (Some(content), _) => content.button.set_active(variant.selected),
+            (None, Some(group)) if variant.selected => group.set_active(true),
+            _ => (),
+        }
+
+        self.syncing.set(syncing);
+    }
+
+    /// Applies a batch of changes to a multiple selection, reporting them as one change.
+    ///
+    /// Each variant is selected if `func` returns `true` for it, or is deselected otherwise.
+    fn batch<F: Fn(&Variant<T>) -> bool>(inner: &Rc<Self>, func: F) {
+        if !inner.multiple {
+            return;
+        }
+
+        let selected = inner.variants.borrow().iter().map(func).collect::<Vec<bool>>();
+
+        let silent = inner.silent.replace(true);
+        inner.pending.set(false);
+
+        for (position, selected) in selected.into_iter().enumerate() {
+            Inner::select_at(inner, position, selected);
+        }
+
+        inner.silent.set(silent);
+
+        if inner.pending.replace(false) {
+            inner.toggled();
+        }
+    }
+
+    /// The events of every selected variant.
+    fn selected(&self) -> Vec<T> {
+        self.variants
+            .borrow()
+            .iter()
+            .filter(|variant| variant.selected)
+            .map(|variant| variant.event.clone())
+            .collect()
+    }
+
+    /// Describes a variant to assistive technologies as a radio button, or as a check box
+    /// with multiple selection.
+    fn describe(&self, child: &gtk::FlowBoxChild, variant: &SelectionVariantBuf<T>) {
+        if let Some(accessible) = child.accessible() {
+            accessible.set_role(if self.multiple {
+                atk::Role::CheckBox
+            } else {
+                atk::Role::RadioButton
+            });
+            accessible.set_name(&variant.name);
+            accessible.set_description(variant.description.as_deref().unwrap_or(""));
+        }
+    }
+
+    /// Records the position of the variant of each child, if variants have been inserted,
+    /// removed, or reordered since the positions were last recorded.
+    fn index(&self) {
+        let mut positions = self.positions.borrow_mut();
+        if positions.is_empty() {
+            let variants = self.variants.borrow();
+            positions.extend(variants.iter().map(|variant| variant.child.clone()).zip(0..));
+        }
+    }
+
+    /// Relates the variants to each other as members of the same radio group.
+    fn relate_group(&self) {
+        if self.multiple {
+            return;
+        }
+
+        selection::relate_group(self.variants.borrow().iter().map(|variant| &variant.child));
+    }
+
+    /// Whether the variant of the child matches the filter query.
+    fn matches(&self, child: &gtk::FlowBoxChild) -> bool {
+        let query = self.query.borrow();
+        if query.is_empty() {
+            return true;
+        }
+
+        self.variants
+            .borrow()
+            .iter()
+            .find(|variant| &variant.child == child)
+            .map_or(true, |variant| variant.matches(&query))
+    }
+
+    /// The flow box child of the active variant.
+    fn active_child(&self) -> Option<gtk::FlowBoxChild> {
+        self.variants
+            .borrow()
+            .iter()
+            .find(|variant| variant.selected)
+            .map(|variant| variant.child.clone())
+    }
+
+    /// The position of the variant associated with `event`.
+    fn position_of(&self, event: &T) -> Option<usize>
+    where
+        T: PartialEq,
+    {
+        self.variants.borrow().iter().position(|variant| &variant.event == event)
+    }
+}
+
+/// The scrolled window which the images of the variants shrink to fit within.
+struct Adaptive {
+    scrolled_window: glib::WeakRef<gtk::ScrolledWindow>,
+    min_shrink:      f64,
+    /// Updates the images when the scrolled window is resized.
+    resized:         glib::SignalHandlerId,
+    /// The horizontal scrollbar policy of the scrolled window before it was adaptive.
+    policy:          gtk::PolicyType,
+}
+
+/// A variant in the selection, and the child which displays it.
+struct Variant<T> {
+    child:    gtk::FlowBoxChild,
+    flow_box: gtk::FlowBox,
+    /// The widgets within the child, which only exist near the viewport when virtualized.
+    content:  Option<Content>,
+    name:     String,
+    subtitle: Option<String>,
+    badge:    Option<String>,
+    terms:    Vec<String>,
+    source:   Option<ImageSource>,
+    size:     Option<(i32, i32)>,
+    loading:  Option<gio::Cancellable>,
+    loaded:   bool,
+    /// The animation which is playing in place of the image, until it is removed.
+    playing:  Option<gtk::TickCallbackId>,
+    selected: bool,
+    event:    T,
+}
+
+/// The widgets within the child of a variant.
+struct Content {
+    event_box: gtk::EventBox,
+    button:    gtk::CheckButton,
+    image:     gtk::Image,
+    badge:     gtk::Label,
+    label:     gtk::Label,
+    subtitle:  gtk::Label,
+    /// Displays the preview on long-press, and is released with the widgets.
+    _gesture:  gtk::GestureLongPress,
+}
+
+impl<T> Variant<T> {
+    /// Records the name, subtitle, badge, and the terms which the variant may be searched by,
+    /// and displays its tooltip and sensitivity.
+    ///
+    /// The reason an insensitive variant is unavailable is appended to its tooltip.
+    fn present(&mut self, variant: &SelectionVariantBuf<T>) {
+        self.name = variant.name.clone();
+        self.subtitle = variant.subtitle.clone();
+        self.badge = variant.badge.clone();
+        self.terms = std::iter::once(&variant.name)
+            .chain(variant.keywords.iter())
+            .map(|term| term.to_lowercase())
+            .collect();
+        self.child.set_sensitive(variant.sensitive);
+
+        let reason = if variant.sensitive { None } else { variant.unavailable_reason.as_deref() };
+        let tooltip = match (variant.tooltip.as_deref(), reason) {
+            (Some(tooltip), Some(reason)) => Some([tooltip, reason].join("\n")),
+            (tooltip, reason) => tooltip.or(reason).map(String::from),
+        };
+
+        self.child.set_tooltip_text(tooltip.as_deref());
+        self.display();
+    }
+
+    /// Whether the name or the keywords of the variant contain every word of the query.
+    fn matches(&self, query: &[String]) -> bool {
+        query.iter().all(|word| self.terms.iter().any(|term| term.contains(word)))
+    }
+
+    /// Displays the name, subtitle, and badge of the variant, if it has widgets.
+    fn display(&self) {
+        let content = match self.content.as_ref() {
+            Some(content) => content,
+            None => return,
+        };
+
+        content.label.set_text(&self.name);
+        content.subtitle.set_text(self.subtitle.as_deref().unwrap_or(""));
+        content.subtitle.set_visible(self.subtitle.is_some());
+        content.badge.set_text(self.badge.as_deref().unwrap_or(""));
+        content.badge.set_visible(self.badge.is_some());
+
+        if let Some(accessible) = content.button.accessible() {
+            accessible.set_name(&self.name);
+        }
+    }
+
+    /// Displays the placeholder, and begins loading the variant's image in the background.
+    ///
+    /// Images with a requested size are loaded at the widget's scale factor. The placeholder
+    /// remains if the image fails to load, and `on_error` is invoked with the reason why.
+    /// Nothing is loaded until the variant has widgets.
+    fn load<E: FnOnce(glib::Error) + 'static>(
+        &mut self,
+        placeholder: Option<&Pixbuf>,
+        fit: ImageFit,
+        shrink: f64,
+        on_error: E,
+    ) {
+        self.release(placeholder, shrink);
+
+        let image = match self.content.as_ref() {
+            Some(content) => content.image.clone(),
+            None => return,
+        };
+
+        self.loaded = true;
+
+        let source = match self.source.as_ref() {
+            Some(source) => source,
+            None => return,
+        };
+
+        let (size, scale) = self.load_size(&image, shrink);
+
+        let cancellable = gio::Cancellable::new();
+        let cancellable_ = cancellable.clone();
+        let image = image.downgrade();
+        source.load_async(size, fit, &cancellable, move |result| {
+            if cancellable_.is_cancelled() {
+                return;
+            }
+
+            match result {
+                Ok(pixbuf) => {
+                    if let Some(image) = image.upgrade() {
+                        let surface = pixbuf.create_surface(scale, image.window().as_ref());
+                        image.set_from_surface(surface.as_ref());
+                    }
+                }
+                Err(why) => on_error(why),
+            }
+        });
+
+        self.loading = Some(cancellable);
+    }
+
+    /// Plays the animation in place of the variant's image, scaling its frames to the size of
+    /// the paused image.
+    fn play(&mut self, animation: &PixbufAnimation, fit: ImageFit, shrink: f64) {
+        let image = match self.content.as_ref() {
+            Some(content) => content.image.clone(),
+            None => return,
+        };
+
+        // The first frame must not replace the animation if it finishes loading afterwards.
+        self.cancel_load();
+        self.loading = None;
+
+        let (size, scale) = self.load_size(&image, shrink);
+        self.playing = Some(loader::play(animation, &image, size, fit, scale));
+    }
+
+    /// Stops the animation which is playing in place of the variant's image, if there is one.
+    fn stop(&mut self) {
+        if let Some(playing) = self.playing.take() {
+            playing.remove();
+        }
+    }
+
+    /// Displays the placeholder in place of the variant's image, releasing the image.
+    fn release(&mut self, placeholder: Option<&Pixbuf>, shrink: f64) {
+        self.cancel_load();
+        self.stop();
+        self.loaded = false;
+        self.loading = None;
+
+        if let Some(content) = self.content.as_ref() {
+            let (width, height) = self.size_request(shrink);
+            content.image.set_size_request(width, height);
+            content.image.set_from_pixbuf(placeholder);
+        }
+    }
+
+    /// Destroys the widgets within the child, releasing the image.
+    fn clear(&mut self) {
+        self.cancel_load();
+        self.stop();
+        self.loaded = false;
+        self.loading = None;
+
+        if let Some(content) = self.content.take() {
+            unsafe { content.event_box.destroy() };
+        }
+    }
+
+    /// The requested size of the image, shrunk by the adaptive layout, or `(-1, -1)` if the
+    /// image has no requested size.
+    fn size_request(&self, shrink: f64) -> (i32, i32) {
+        self.size.map_or((-1, -1), |(width, height)| {
+            (shrink_side(width, shrink), shrink_side(height, shrink))
+        })
+    }
+
+    /// The size which the image is loaded at in device pixels, and the scale factor which it
+    /// is loaded at.
+    ///
+    /// Images without a requested size are loaded at their own size, regardless of the scale
+    /// factor.
+    fn load_size(&self, image: &gtk::Image, shrink: f64) -> ((i32, i32), i32) {
+        let (width, height) = self.size_request(shrink);
+        let scale = if self.size.is_some() { image.scale_factor() } else { 1 };
+        ((scale_side(width, scale), scale_side(height, scale)), scale)
+    }
+
+    /// Cancels the pending load of the variant's image, if there is one.
+    fn cancel_load(&self) {
+        if let Some(cancellable) = self.loading.as_ref() {
+            cancellable.cancel();
+        }
+    }
+}
+
+/// The position of a variant within its flow box, given its position among all variants.
+fn position_in<T>(variants: &[Variant<T>], flow_box: &gtk::FlowBox, position: usize) -> usize {
+    variants[..position].iter().filter(|variant| &variant.flow_box == flow_box).count()
+}
+
+/// Shrinks a side by the adaptive layout, unless the side is unconstrained.
+fn shrink_side(side: i32, shrink: f64) -> i32 {
+    if side < 1 {
+        side
+    } else {
+        ((f64::from(side) * shrink).round() as i32).max(1)
+    }
+}
+
+/// Multiplies a side by the scale factor, unless the side is unconstrained.
+fn scale_side(side: i32, scale: i32) -> i32 {
+    if side < 1 {
+        side
+    } else {
+        side * scale
+    }
+}
+
+/// How an image is scaled to the size requested by its variant.
+///
+/// In either case, the aspect ratio of the image is preserved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFit {
+    /// Scales the image to fit within the requested size.
+    Fit,
+    /// Scales the image to cover the requested size, cropping the edges which overflow.
+    Crop,
+}
+
+impl Default for ImageFit {
+    fn default() -> Self { ImageFit::Fit }
+}
+
+/// The source of an image to display for a variant.
+#[derive(Clone, Copy)]
+pub enum ImageSrc<'a> {
+    /// A path to an image file.
+    File(&'a str),
+    /// A path to an image in a registered `gio::Resource`.
+    Resource(&'a str),
+    /// A URI to an image which is readable by `gio::File`.
+    Uri(&'a str),
+    /// An image file.
+    GFile(&'a gio::File),
+    /// An encoded image in memory.
+    Bytes(&'a [u8]),
+    /// The name of an icon in the icon theme.
+    IconName(&'a str),
+    /// An icon, such as one from a `.desktop` file.
+    Icon(&'a gio::Icon),
+    /// An image which has already been decoded.
+    Pixbuf(&'a Pixbuf),
+    /// An animation, such as a GIF, whose frames are scaled to the requested size.
+    Animation(&'a PixbufAnimation),
+}
+
+/// A variant of an `ImageSelection`, borrowed from static or already-owned data.
+///
+/// Fields are added to this struct as the selection gains features, which breaks struct
+/// literals that name every field. Variants built with a `SelectionVariantBuilder` are
+/// unaffected by new fields.
+pub struct SelectionVariant<'a, T> {
+    pub name:               &'a str,
+    /// Additional terms which the variant may be found by when filtering.
+    pub keywords:           &'a [&'a str],
+    /// The heading of the section which the variant is displayed in.
+    pub group:              Option<&'a str>,
+    /// A secondary line of text displayed beneath the name.
+    pub subtitle:           Option<&'a str>,
+    /// A short label overlaid on the corner of the image, such as "New" or "Recommended".
+    pub badge:              Option<&'a str>,
+    /// Text displayed when hovering over the variant.
+    pub tooltip:            Option<&'a str>,
+    /// Describes the variant to assistive technologies.
+    pub description:        Option<&'a str>,
+    pub image:              Option<ImageSrc<'a>>,
+    pub size_request:       Option<(i32, i32)>,
+    pub active:             bool,
+    /// Whether the variant may be selected by the user, or is greyed out.
+    pub sensitive:          bool,
+    /// Why the variant is insensitive, which is displayed in its tooltip.
+    pub unavailable_reason: Option<&'a str>,
+    pub event:              T,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scale_side, shrink_side};
+
+    #[test]
+    fn shrinking_rounds_to_at_least_one_pixel() {
+        assert_eq!(shrink_side(100, 0.5), 50);
+        assert_eq!(shrink_side(101, 0.5), 51);
+        assert_eq!(shrink_side(3, 0.0625), 1);
+        assert_eq!(shrink_side(100, 1.0), 100);
+    }
+
+    #[test]
+    fn unconstrained_sides_are_not_shrunk_or_scaled() {
+        assert_eq!(shrink_side(-1, 0.5), -1);
+        assert_eq!(shrink_side(0, 0.5), 0);
+        assert_eq!(scale_side(-1, 2), -1);
+        assert_eq!(scale_side(0, 2), 0);
+    }
+
+    #[test]
+    fn sides_are_scaled_by_the_scale_factor() {
+        assert_eq!(scale_side(64, 1), 64);
+        assert_eq!(scale_side(64, 2), 128);
+    }
+}
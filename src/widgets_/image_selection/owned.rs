@@ -0,0 +1,190 @@
+//! Owned variants of the `ImageSelection`, which may be built from runtime data.
+
+use super::{ImageSrc, SelectionVariant};
+use gdk_pixbuf::{Pixbuf, PixbufAnimation};
+use std::path::PathBuf;
+
+/// An owned `ImageSrc`.
+#[derive(Clone)]
+pub enum ImageSrcBuf {
+    /// A path to an image file.
+    File(PathBuf),
+    /// A path to an image in a registered `gio::Resource`.
+    Resource(String),
+    /// A URI to an image which is readable by `gio::File`.
+    Uri(String),
+    /// An image file.
+    GFile(gio::File),
+    /// An encoded image in memory.
+    Bytes(glib::Bytes),
+    /// The name of an icon in the icon theme.
+    IconName(String),
+    /// An icon, such as one from a `.desktop` file.
+    Icon(gio::Icon),
+    /// An image which has already been decoded.
+    Pixbuf(Pixbuf),
+    /// An animation, such as a GIF, whose frames are scaled to the requested size.
+    Animation(PixbufAnimation),
+}
+
+impl<'a> From<ImageSrc<'a>> for ImageSrcBuf {
+    fn from(src: ImageSrc<'a>) -> Self {
+        match src {
+            ImageSrc::File(path) => ImageSrcBuf::File(PathBuf::from(path)),
+            ImageSrc::Resource(res) => ImageSrcBuf::Resource(res.to_owned()),
+            ImageSrc::Uri(uri) => ImageSrcBuf::Uri(uri.to_owned()),
+            ImageSrc::GFile(file) => ImageSrcBuf::GFile(file.clone()),
+            ImageSrc::Bytes(bytes) => ImageSrcBuf::Bytes(glib::Bytes::from(bytes)),
+            ImageSrc::IconName(name) => ImageSrcBuf::IconName(name.to_owned()),
+            ImageSrc::Icon(icon) => ImageSrcBuf::Icon(icon.clone()),
+            ImageSrc::Pixbuf(pixbuf) => ImageSrcBuf::Pixbuf(pixbuf.clone()),
+            ImageSrc::Animation(animation) => ImageSrcBuf::Animation(animation.clone()),
+        }
+    }
+}
+
+impl From<PathBuf> for ImageSrcBuf {
+    fn from(path: PathBuf) -> Self { ImageSrcBuf::File(path) }
+}
+
+/// An owned `SelectionVariant`, which is most easily created with a
+/// `SelectionVariantBuilder`.
+#[derive(Clone)]
+pub struct SelectionVariantBuf<T> {
+    pub name:               String,
+    pub keywords:           Vec<String>,
+    pub group:              Option<String>,
+    pub subtitle:           Option<String>,
+    pub badge:              Option<String>,
+    pub tooltip:            Option<String>,
+    pub description:        Option<String>,
+    pub image:              Option<ImageSrcBuf>,
+    pub size_request:       Option<(i32, i32)>,
+    pub active:             bool,
+    pub sensitive:          bool,
+    pub unavailable_reason: Option<String>,
+    pub event:              T,
+}
+
+impl<'a, T: Clone> From<&SelectionVariant<'a, T>> for SelectionVariantBuf<T> {
+    fn from(variant: &SelectionVariant<'a, T>) -> Self {
+        Self {
+            name:               variant.name.to_owned(),
+            keywords:           variant.keywords.iter().copied().map(String::from).collect(),
+            group:              variant.group.map(String::from),
+            subtitle:           variant.subtitle.map(String::from),
+            badge:              variant.badge.map(String::from),
+            tooltip:            variant.tooltip.map(String::from),
+            description:        variant.description.map(String::from),
+            image:              variant.image.map(ImageSrcBuf::from),
+            size_request:       variant.size_request,
+            active:             variant.active,
+            sensitive:          variant.sensitive,
+            unavailable_reason: variant.unavailable_reason.map(String::from),
+            event:              variant.event.clone(),
+        }
+    }
+}
+
+impl<T: Clone> From<&SelectionVariantBuf<T>> for SelectionVariantBuf<T> {
+    fn from(variant: &SelectionVariantBuf<T>) -> Self { variant.clone() }
+}
+
+impl<T> From<SelectionVariantBuilder<T>> for SelectionVariantBuf<T> {
+    fn from(builder: SelectionVariantBuilder<T>) -> Self { builder.build() }
+}
+
+/// Builds a `SelectionVariantBuf` from runtime data, such as the files of a directory.
+///
+/// The variant is sensitive and inactive unless specified otherwise.
+pub struct SelectionVariantBuilder<T>(SelectionVariantBuf<T>);
+
+impl<T> SelectionVariantBuilder<T> {
+    pub fn new(name: impl Into<String>, event: T) -> Self {
+        Self(SelectionVariantBuf {
+            name:               name.into(),
+            keywords:           Vec::new(),
+            group:              None,
+            subtitle:           None,
+            badge:              None,
+            tooltip:            None,
+            description:        None,
+            image:              None,
+            size_request:       None,
+            active:             false,
+            sensitive:          true,
+            unavailable_reason: None,
+            event,
+        })
+    }
+
+    /// Additional terms which the variant may be found by when filtering.
+    pub fn keywords<I, S>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.0.keywords.extend(keywords.into_iter().map(Into::into));
+        self
+    }
+
+    /// The heading of the section which the variant is displayed in.
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.0.group = Some(group.into());
+        self
+    }
+
+    /// A secondary line of text displayed beneath the name.
+    pub fn subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.0.subtitle = Some(subtitle.into());
+        self
+    }
+
+    /// A short label overlaid on the corner of the image.
+    pub fn badge(mut self, badge: impl Into<String>) -> Self {
+        self.0.badge = Some(badge.into());
+        self
+    }
+
+    /// Text displayed when hovering over the variant.
+    pub fn tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.0.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Describes the variant to assistive technologies.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.0.description = Some(description.into());
+        self
+    }
+
+    pub fn image(mut self, image: impl Into<ImageSrcBuf>) -> Self {
+        self.0.image = Some(image.into());
+        self
+    }
+
+    pub fn size_request(mut self, width: i32, height: i32) -> Self {
+        self.0.size_request = Some((width, height));
+        self
+    }
+
+    pub fn active(mut self, active: bool) -> Self {
+        self.0.active = active;
+        self
+    }
+
+    /// Whether the variant may be selected by the user, or is greyed out.
+    pub fn sensitive(mut self, sensitive: bool) -> Self {
+        self.0.sensitive = sensitive;
+        self
+    }
+
+    /// Marks the variant as insensitive, explaining why in its tooltip.
+    pub fn unavailable(mut self, reason: impl Into<String>) -> Self {
+        self.0.sensitive = false;
+        self.0.unavailable_reason = Some(reason.into());
+        self
+    }
+
+    pub fn build(self) -> SelectionVariantBuf<T> { self.0 }
+}
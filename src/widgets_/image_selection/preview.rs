@@ -0,0 +1,140 @@
+//! Hovering of variants, and their previews displayed in a popover on hover or long-press.
+
+use super::{ImageSelection, Inner};
+use gtk::prelude::*;
+use std::{rc::Rc, time::Duration};
+
+/// How long the pointer must rest on a variant before its preview is displayed.
+const HOVER_DELAY: Duration = Duration::from_millis(600);
+
+/// Creates the preview widget for the event of a variant.
+pub(super) type PreviewFn<T> = Box<dyn Fn(T) -> Option<gtk::Widget>>;
+
+impl<T: Clone + 'static> ImageSelection<T> {
+    /// Displays a larger preview of a variant in a popover when the pointer rests on it, or
+    /// when it is long-pressed on a touchscreen.
+    ///
+    /// `func` creates the preview widget for the event of the variant, or returns `None` if
+    /// the variant has no preview. A new widget is requested each time a preview is shown.
+    pub fn set_preview<F: Fn(T) -> Option<gtk::Widget> + 'static>(&self, func: F) {
+        *self.inner.preview_cb.borrow_mut() = Some(Box::new(func));
+    }
+}
+
+impl<T: Clone + 'static> Inner<T> {
+    /// Tracks the hovered variant, and displays the preview of the child's variant on hover
+    /// and long-press.
+    ///
+    /// The returned gesture must be kept alive for as long as the event box.
+    pub(super) fn watch_hover(
+        inner: &Rc<Self>,
+        child: &gtk::FlowBoxChild,
+        event_box: &gtk::EventBox,
+    ) -> gtk::GestureLongPress {
+        let inner_ = Rc::downgrade(inner);
+        let child_ = child.downgrade();
+        event_box.connect_enter_notify_event(move |_, _| {
+            if let (Some(inner), Some(child)) = (inner_.upgrade(), child_.upgrade()) {
+                *inner.hovered.borrow_mut() = Some(child.clone());
+                Inner::queue_update(&inner);
+                Inner::hover(&inner, &child);
+            }
+
+            gtk::Inhibit(false)
+        });
+
+        let inner_ = Rc::downgrade(inner);
+        event_box.connect_leave_notify_event(move |_, crossing| {
+            // Moving onto a widget within the variant does not leave it.
+            if crossing.detail() != gdk::NotifyType::Inferior {
+                if let Some(inner) = inner_.upgrade() {
+                    *inner.hovered.borrow_mut() = None;
+                    Inner::queue_update(&inner);
+                    inner.hide_preview();
+                }
+            }
+
+            gtk::Inhibit(false)
+        });
+
+        let gesture = gtk::GestureLongPress::new(event_box);
+        gesture.set_touch_only(true);
+
+        let inner_ = Rc::downgrade(inner);
+        let child_ = child.downgrade();
+        gesture.connect_pressed(move |_, _, _| {
+            if let (Some(inner), Some(child)) = (inner_.upgrade(), child_.upgrade()) {
+                inner.show_preview(&child, true);
+            }
+        });
+
+        gesture
+    }
+
+    /// Displays the preview of the child's variant once the pointer has rested on it.
+    fn hover(inner: &Rc<Self>, child: &gtk::FlowBoxChild) {
+        inner.hide_preview();
+
+        if inner.preview_cb.borrow().is_none() {
+            return;
+        }
+
+        let inner_ = Rc::downgrade(inner);
+        let child = child.downgrade();
+        let source = glib::timeout_add_local(HOVER_DELAY, move || {
+            if let (Some(inner), Some(child)) = (inner_.upgrade(), child.upgrade()) {
+                *inner.hover.borrow_mut() = None;
+                inner.show_preview(&child, false);
+            }
+
+            glib::Continue(false)
+        });
+
+        *inner.hover.borrow_mut() = Some(source);
+    }
+
+    /// Displays the preview of the child's variant in a popover pointing to the child.
+    ///
+    /// A modal preview remains until it is dismissed, rather than when the pointer leaves.
+    fn show_preview(&self, child: &gtk::FlowBoxChild, modal: bool) {
+        self.hide_preview();
+
+        let event =
+            self.variants.borrow().iter().find(|v| &v.child == child).map(|v| v.event.clone());
+
+        let widget = event.and_then(|event| {
+            self.preview_cb.borrow().as_ref().and_then(|func| func(event))
+        });
+
+        let widget = match widget {
+            Some(widget) => widget,
+            None => return,
+        };
+
+        widget.show_all();
+
+        let popover = cascade! {
+            gtk::Popover::new(Some(child));
+            ..set_modal(modal);
+            ..set_position(gtk::PositionType::Top);
+            ..add(&widget);
+            ..connect_closed(|popover| unsafe { popover.destroy() });
+        };
+
+        popover.popup();
+        *self.preview.borrow_mut() = Some(popover);
+    }
+
+    /// Hides the displayed preview, and cancels any preview which is about to be displayed.
+    pub(super) fn hide_preview(&self) {
+        let source = self.hover.borrow_mut().take();
+        if let Some(source) = source {
+            glib::source_remove(source);
+        }
+
+        let popover = self.preview.borrow_mut().take();
+        if let Some(popover) = popover {
+            popover.popdown();
+        }
+    }
+}
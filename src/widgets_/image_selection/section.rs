@@ -0,0 +1,142 @@
+//! Headed sections of variants within the `ImageSelection`.
+
+use gtk::prelude::*;
+
+/// A group of variants, displayed in its own flow box beneath an optional heading.
+pub(super) struct Section {
+    pub name:     Option<String>,
+    pub heading:  Option<gtk::Label>,
+    pub flow_box: gtk::FlowBox,
+    pub tile:     Option<gtk::FlowBoxChild>,
+}
+
+impl Section {
+    pub fn new(name: Option<&str>) -> Self {
+        let heading = name.map(|name| {
+            gtk::LabelBuilder::new()
+                .label(&format!("<b>{}</b>", glib::markup_escape_text(name)))
+                .use_markup(true)
+                .xalign(0.0)
+                .no_show_all(true)
+                .build()
+        });
+
+        let flow_box = gtk::FlowBoxBuilder::new()
+            .can_focus(true)
+            .focus_on_click(false)
+            .homogeneous(true)
+            .selection_mode(gtk::SelectionMode::None)
+            .no_show_all(true)
+            .build();
+
+        Self { name: name.map(String::from), heading, flow_box, tile: None }
+    }
+
+    /// Limits the columns of the flow box, and spaces its rows and columns.
+    pub fn arrange(&self, (min, max): (u32, u32), (row, column): (u32, u32)) {
+        self.flow_box.set_min_children_per_line(min);
+        self.flow_box.set_max_children_per_line(max);
+        self.flow_box.set_row_spacing(row);
+        self.flow_box.set_column_spacing(column);
+    }
+
+    /// Shows or hides the heading and the flow box of the section.
+    pub fn set_visible(&self, visible: bool) {
+        if let Some(heading) = self.heading.as_ref() {
+            heading.set_visible(visible);
+        }
+
+        self.flow_box.set_visible(visible);
+    }
+
+    /// The first child which may receive the focus.
+    pub fn first_child(&self) -> Option<gtk::FlowBoxChild> { self.focusable().next() }
+
+    /// The last child which may receive the focus.
+    pub fn last_child(&self) -> Option<gtk::FlowBoxChild> { self.focusable().last() }
+
+    /// The displayed children within `margin` pixels above or below the visible area of the
+    /// viewport.
+    ///
+    /// Children are ordered by their position, so the first child near the visible area is
+    /// found by a binary search rather than by measuring every child above it.
+    pub fn near(&self, viewport: &gtk::ScrolledWindow, margin: i32) -> Vec<gtk::FlowBoxChild> {
+        let top = match self.flow_box.translate_coordinates(viewport, 0, 0) {
+            Some((_, top)) if self.flow_box.is_visible() => top,
+            _ => return Vec::new(),
+        };
+
+        // The area relative to the top of the flow box.
+        let (start, end) = (-top - margin, viewport.allocated_height() - top + margin);
+        if end < 0 || start > self.flow_box.allocated_height() {
+            return Vec::new();
+        }
+
+        let top_of = |child: &gtk::FlowBoxChild| {
+            child.translate_coordinates(&self.flow_box, 0, 0).map_or(0, |(_, top)| top)
+        };
+
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match self.displayed_from(middle, high) {
+                Some((index, child)) if top_of(&child) + child.allocated_height() < start => {
+                    low = index + 1;
+                }
+                _ => high = middle,
+            }
+        }
+
+        let mut near = Vec::new();
+        while let Some((index, child)) = self.displayed_from(low, i32::MAX) {
+            if top_of(&child) > end {
+                break;
+            }
+
+            near.push(child);
+            low = index + 1;
+        }
+
+        near
+    }
+
+    /// The first displayed child from the index `from`, and before the index `to`.
+    fn displayed_from(&self, from: i32, to: i32) -> Option<(i32, gtk::FlowBoxChild)> {
+        (from..to)
+            .map(|index| self.flow_box.child_at_index(index).map(|child| (index, child)))
+            .take_while(Option::is_some)
+            .flatten()
+            .find(|(_, child)| child.is_visible() && child.is_child_visible())
+    }
+
+    /// The number of children in the flow box, found by an exponential search.
+    fn len(&self) -> i32 {
+        let exists = |index| self.flow_box.child_at_index(index).is_some();
+        if !exists(0) {
+            return 0;
+        }
+
+        let (mut low, mut high) = (0, 1);
+        while exists(high) {
+            low = high;
+            high *= 2;
+        }
+
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if exists(middle) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        high
+    }
+
+    /// Children which are neither filtered nor insensitive.
+    fn focusable(&self) -> impl Iterator<Item = gtk::FlowBoxChild> {
+        crate::widgets::iter_from::<gtk::FlowBoxChild, _>(&self.flow_box)
+            .filter(|child| child.is_child_visible() && child.is_sensitive())
+    }
+}
@@ -0,0 +1,43 @@
+//! Binding of the `ImageSelection` to a GSettings key.
+
+use super::ImageSelection;
+use crate::widgets_::selection;
+use gio::Settings;
+
+impl<T: Clone + PartialEq + 'static> ImageSelection<T> {
+    /// Binds the selection to a GSettings key, in both directions.
+    ///
+    /// Each variant is associated with a value of the key by `values`. The variant of the
+    /// current value is selected immediately, changes to the selection are written to the key,
+    /// and the selection follows the key when it is changed elsewhere, such as by
+    /// `gsettings set`. Synchronizing from the key does not invoke the selection's callbacks.
+    ///
+    /// A radio group binds to a string or enum key. A multiple selection binds to a string
+    /// array key, which contains the values of every selected variant.
+    pub fn bind_settings(&self, settings: &Settings, key: &str, values: &[(T, &str)]) {
+        let weak = self.downgrade();
+        let write = selection::bind_settings(
+            &self.container,
+            settings,
+            key,
+            values,
+            self.inner.multiple,
+            move |events| {
+                if let Some(selection) = weak.upgrade().map(Self::from_parts) {
+                    selection.sync_from_settings(events);
+                }
+            },
+        );
+
+        self.connect_selection_changed(write);
+    }
+
+    /// Selects the variants associated with the current value of the key.
+    fn sync_from_settings(&self, events: &[T]) {
+        if self.inner.multiple {
+            self.set_selected_silent(events);
+        } else if let Some(event) = events.first() {
+            self.set_active_silent(event.clone());
+        }
+    }
+}
@@ -1,11 +1,17 @@
+mod color_selection;
 mod image_selection;
 mod revealing_button;
+mod selection;
 mod uuid_entry;
 mod variant_toggler;
 
 pub use self::{
-    image_selection::{ImageSelection, ImageSrc, SelectionVariant},
-    revealing_button::RevealingButton,
+    color_selection::{ColorSelection, ColorVariant, SwatchShape},
+    image_selection::{
+        ImageFit, ImageSelection, ImageSrc, ImageSrcBuf, SelectionVariant, SelectionVariantBuf,
+        SelectionVariantBuilder,
+    },
+    revealing_button::{ExpansionStore, RevealingButton, RevealingGroup},
     uuid_entry::UuidEntry,
     variant_toggler::{ToggleVariant, VariantToggler},
 };
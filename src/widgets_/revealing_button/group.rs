@@ -0,0 +1,130 @@
+//! Accordion groups of `RevealingButton`s.
+
+use super::RevealingButton;
+use gtk::prelude::*;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+/// A group of `RevealingButton`s, of which at most one is revealed at a time.
+///
+/// Revealing the child of a button in the group collapses every other button in the group.
+#[derive(Clone, Default)]
+pub struct RevealingGroup(Rc<Inner>);
+
+#[derive(Default)]
+struct Inner {
+    members:           RefCell<Vec<Member>>,
+    collapsing:        RefCell<Vec<glib::WeakRef<gtk::Revealer>>>,
+    destroy_collapsed: Cell<bool>,
+}
+
+/// A button in the group, and the handlers which connect it to the group.
+struct Member {
+    revealer:  glib::WeakRef<gtk::Revealer>,
+    /// Collapses the other members when the button is revealed.
+    revealed:  glib::SignalHandlerId,
+    /// Destroys the child of the button once the group has collapsed it.
+    collapsed: glib::SignalHandlerId,
+}
+
+impl RevealingGroup {
+    pub fn new() -> Self { Self::default() }
+
+    /// Destroys the generated children of buttons which the group collapses, once they have
+    /// finished collapsing, so that they are generated again on their next reveal.
+    pub fn set_destroy_collapsed(&self, destroy: bool) { self.0.destroy_collapsed.set(destroy); }
+
+    /// Collapses every member of the group other than the revealer which was revealed.
+    fn collapse_others(&self, revealed: &gtk::Revealer) {
+        let others = self
+            .0
+            .members
+            .borrow()
+            .iter()
+            .filter_map(|member| member.revealer.upgrade())
+            .filter(|member| member != revealed && member.reveals_child())
+            .collect::<Vec<_>>();
+
+        for member in others {
+            if self.0.destroy_collapsed.get() {
+                self.0.collapsing.borrow_mut().push(member.downgrade());
+            }
+
+            member.set_reveal_child(false);
+        }
+    }
+
+    /// Destroys the child of a member which the group has finished collapsing.
+    fn collapsed(&self, revealer: &gtk::Revealer) {
+        let collapsing = {
+            let mut collapsing = self.0.collapsing.borrow_mut();
+            let position = collapsing
+                .iter()
+                .position(|member| member.upgrade().as_ref() == Some(revealer));
+            position.map(|position| collapsing.remove(position))
+        };
+
+        if collapsing.is_some() && !revealer.reveals_child() {
+            if let Some(child) = revealer.child() {
+                unsafe { child.destroy() }
+            }
+        }
+    }
+}
+
+impl RevealingButton {
+    /// Adds the button to an accordion group, in which at most one button is revealed.
+    ///
+    /// Joining a group which the button is already a member of has no effect.
+    pub fn join_group(&self, group: &RevealingGroup) {
+        let mut members = group.0.members.borrow_mut();
+        members.retain(|member| member.revealer.upgrade().is_some());
+
+        if members.iter().any(|member| member.revealer.upgrade().as_ref() == Some(&self.revealer)) {
+            return;
+        }
+
+        let group_ = group.clone();
+        let revealed = self.revealer.connect_reveal_child_notify(move |revealer| {
+            if revealer.reveals_child() {
+                group_.collapse_others(revealer);
+            }
+        });
+
+        let group_ = group.clone();
+        let collapsed = self.revealer.connect_child_revealed_notify(move |revealer| {
+            if !revealer.is_child_revealed() {
+                group_.collapsed(revealer);
+            }
+        });
+
+        members.push(Member { revealer: self.revealer.downgrade(), revealed, collapsed });
+    }
+
+    /// Removes the button from an accordion group, so that it is revealed and collapsed
+    /// independently of the other members.
+    ///
+    /// Has no effect if the button is not a member of the group.
+    pub fn leave_group(&self, group: &RevealingGroup) {
+        let member = {
+            let mut members = group.0.members.borrow_mut();
+            let position = members
+                .iter()
+                .position(|member| member.revealer.upgrade().as_ref() == Some(&self.revealer));
+            position.map(|position| members.remove(position))
+        };
+
+        if let Some(member) = member {
+            self.revealer.disconnect(member.revealed);
+            self.revealer.disconnect(member.collapsed);
+        }
+
+        group
+            .0
+            .collapsing
+            .borrow_mut()
+            .retain(|collapsing| collapsing.upgrade().as_ref() != Some(&self.revealer));
+    }
+}
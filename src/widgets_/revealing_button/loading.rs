@@ -0,0 +1,117 @@
+//! Asynchronous generation of the child of a `RevealingButton`.
+
+use super::RevealingButton;
+use gtk::prelude::*;
+use std::{cell::RefCell, fmt::Display, future::Future, rc::Rc};
+
+/// The state of a child which is generated asynchronously, until it has been generated.
+pub(super) type Loading = Rc<RefCell<Option<Load>>>;
+
+pub(super) enum Load {
+    /// The child is being generated, and a spinner is displayed in its place.
+    Pending(gio::Cancellable),
+    /// The child could not be generated, and the error is displayed in its place.
+    Failed,
+}
+
+impl RevealingButton {
+    /// Reveals an inner child, and generates it asynchronously if it is missing.
+    ///
+    /// A spinner is revealed in place of the child until the future completes, and the error
+    /// is displayed in its place if the child could not be generated. Collapsing the button
+    /// before the future completes cancels the `gio::Cancellable` given to `func`, and
+    /// discards the result. Collapsing the button while the spinner or the error is displayed
+    /// removes it, so that the child is generated again on the next reveal.
+    pub fn reveal_async<F, Fut, E>(&self, func: F) -> bool
+    where
+        F: FnOnce(gio::Cancellable) -> Fut,
+        Fut: Future<Output = Result<gtk::Widget, E>> + 'static,
+        E: Display,
+    {
+        if self.revealer.reveals_child() {
+            self.revealer.set_reveal_child(false);
+            return false;
+        }
+
+        if self.revealer.child().is_none() {
+            self.revealer.add(&cascade! {
+                gtk::Spinner::new();
+                ..set_halign(gtk::Align::Center);
+                ..set_margin_top(6);
+                ..set_margin_bottom(6);
+                ..start();
+                ..show();
+            });
+
+            let cancellable = gio::Cancellable::new();
+            *self.loading.borrow_mut() = Some(Load::Pending(cancellable.clone()));
+
+            let future = func(cancellable.clone());
+            let revealer = self.revealer.downgrade();
+            let loading = self.loading.clone();
+            glib::MainContext::default().spawn_local(async move {
+                let result = future.await;
+
+                if cancellable.is_cancelled() {
+                    return;
+                }
+
+                let revealer = match revealer.upgrade() {
+                    Some(revealer) => revealer,
+                    None => return,
+                };
+
+                let child = match result {
+                    Ok(child) => {
+                        *loading.borrow_mut() = None;
+                        child
+                    }
+                    Err(why) => {
+                        *loading.borrow_mut() = Some(Load::Failed);
+
+                        let label = gtk::LabelBuilder::new()
+                            .label(&why.to_string())
+                            .wrap(true)
+                            .xalign(0.0)
+                            .build();
+
+                        label.style_context().add_class(&gtk::STYLE_CLASS_ERROR);
+                        label.show();
+                        label.upcast::<gtk::Widget>()
+                    }
+                };
+
+                if let Some(spinner) = revealer.child() {
+                    unsafe { spinner.destroy() }
+                }
+
+                revealer.add(&child);
+            });
+        }
+
+        self.revealer.set_reveal_child(true);
+        true
+    }
+}
+
+/// Cancels the generation of the child when the button collapses, and removes the spinner or
+/// the error which is displayed in place of the child.
+pub(super) fn cancel_on_collapse(revealer: &gtk::Revealer, loading: &Loading) {
+    let loading = loading.clone();
+    revealer.connect_reveal_child_notify(move |revealer| {
+        if revealer.reveals_child() {
+            return;
+        }
+
+        let load = loading.borrow_mut().take();
+        if let Some(load) = load {
+            if let Load::Pending(cancellable) = load {
+                cancellable.cancel();
+            }
+
+            if let Some(placeholder) = revealer.child() {
+                unsafe { placeholder.destroy() }
+            }
+        }
+    });
+}
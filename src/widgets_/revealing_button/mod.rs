@@ -0,0 +1,294 @@
+mod group;
+mod loading;
+mod persist;
+
+pub use self::{group::RevealingGroup, persist::ExpansionStore};
+
+use self::loading::Loading;
+use gdk::keys::constants as key;
+use gtk::prelude::*;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+/// Generates the child widget of a `RevealingButton` when it is expanded.
+type Generator = Rc<RefCell<Option<Box<dyn FnMut() -> gtk::Widget>>>>;
+
+/// The callbacks invoked when a `RevealingButton` is activated.
+type Clicked = Rc<RefCell<Vec<Rc<dyn Fn(gtk::Revealer)>>>>;
+
+/// A widget which reveals a child widget when clicked
+///
+/// The primary widget is displayed at all times, whereas the child widget is generated
+/// on the first reveal. Buttons may join a `RevealingGroup`, so that revealing one button
+/// collapses the others.
+///
+/// The primary widget may be focused, and activated from the keyboard. Activating the button
+/// invokes the callbacks connected with `connect_clicked`, or toggles the button if there are
+/// none. Children which are expensive to generate may be generated asynchronously with
+/// `reveal_async`.
+///
+/// The button may also be expanded and collapsed programmatically, in which case the child is
+/// generated by the function given to `set_generator`. Its expansion state may be persisted
+/// across sessions in an `ExpansionStore`.
+#[derive(AsRef, Deref)]
+pub struct RevealingButton {
+    #[as_ref]
+    #[deref]
+    container: gtk::Container,
+
+    _dropdown_image: gtk::Image,
+
+    pub event_box: gtk::EventBox,
+    pub revealer:  gtk::Revealer,
+
+    clicked:   Clicked,
+    generator: Generator,
+    loading:   Loading,
+}
+
+impl RevealingButton {
+    pub fn new<M>(main_content: M) -> Self
+    where
+        M: FnOnce(&gtk::Image) -> gtk::Widget,
+    {
+        let dropdown_image = gtk::ImageBuilder::new()
+            .icon_name("pan-end-symbolic")
+            .icon_size(gtk::IconSize::Menu.into())
+            .halign(gtk::Align::Start)
+            .valign(gtk::Align::Center)
+            .build();
+
+        let dropdown_image_ = dropdown_image.downgrade();
+        let revealer = cascade! {
+            gtk::Revealer::new();
+            ..connect_reveal_child_notify(move |revealer| {
+                dropdown_image_.upgrade()
+                    .expect("dropdown image did not exist")
+                    .set_from_icon_name(
+                        Some(if revealer.reveals_child() {
+                            "pan-down-symbolic"
+                        } else {
+                            "pan-end-symbolic"
+                        }),
+                        gtk::IconSize::Menu
+                    );
+            });
+        };
+
+        let event_box = cascade! {
+            gtk::EventBoxBuilder::new()
+                .can_focus(true)
+                .hexpand(true)
+                .events(gdk::EventMask::BUTTON_PRESS_MASK | gdk::EventMask::KEY_PRESS_MASK)
+                .build();
+            ..add(&main_content(&dropdown_image));
+        };
+
+        // The focus ring is drawn after the default handler, so that it is not covered by the
+        // children of the event box.
+        let focus_ring = event_box.connect_local("draw", true, |values| {
+            let event_box = values[0].get::<gtk::EventBox>().expect("draw without an event box");
+            let cr = values[1].get::<cairo::Context>().expect("draw without a cairo context");
+
+            if event_box.has_visible_focus() {
+                gtk::render_focus(
+                    &event_box.style_context(),
+                    &cr,
+                    0.0,
+                    0.0,
+                    f64::from(event_box.allocated_width()),
+                    f64::from(event_box.allocated_height()),
+                );
+            }
+
+            Some(false.to_value())
+        });
+
+        if let Err(why) = focus_ring {
+            error!("failed to draw the focus ring of a revealing button: {}", why);
+        }
+
+        let clicked = Clicked::default();
+        let generator = Generator::default();
+
+        let revealer_ = revealer.downgrade();
+        let clicked_ = clicked.clone();
+        let generator_ = generator.clone();
+        // The press is only consumed by the clicked callbacks, so that handlers connected to
+        // the event box still receive presses which merely toggle the button.
+        event_box.connect_button_press_event(move |_, event| {
+            if event.event_type() != gdk::EventType::ButtonPress || event.button() != 1 {
+                return gtk::Inhibit(false);
+            }
+
+            let revealer = revealer_.upgrade().expect("revealer for device did not exist");
+            gtk::Inhibit(activate(&revealer, &clicked_, &generator_))
+        });
+
+        let revealer_ = revealer.downgrade();
+        let clicked_ = clicked.clone();
+        let generator_ = generator.clone();
+        event_box.connect_key_press_event(move |event_box, event| {
+            let revealer = revealer_.upgrade().expect("revealer for device did not exist");
+
+            let (expand, collapse) = if event_box.direction() == gtk::TextDirection::Rtl {
+                (key::Left, key::Right)
+            } else {
+                (key::Right, key::Left)
+            };
+
+            let keyval = event.keyval();
+            let activates = if [key::Return, key::KP_Enter, key::space, key::KP_Space]
+                .contains(&keyval)
+            {
+                true
+            } else if keyval == expand {
+                !revealer.reveals_child()
+            } else if keyval == collapse {
+                revealer.reveals_child()
+            } else {
+                return gtk::Inhibit(false);
+            };
+
+            if activates {
+                activate(&revealer, &clicked_, &generator_);
+            }
+
+            gtk::Inhibit(activates)
+        });
+
+        let loading = Loading::default();
+        loading::cancel_on_collapse(&revealer, &loading);
+
+        let container = cascade! {
+            gtk::Box::new(gtk::Orientation::Vertical, 4);
+            ..set_border_width(12);
+            ..set_can_focus(false);
+            ..add(&event_box);
+            ..add(&revealer);
+        };
+
+        Self {
+            container: container.upcast::<gtk::Container>(),
+            _dropdown_image: dropdown_image,
+            event_box,
+            revealer,
+            clicked,
+            generator,
+            loading,
+        }
+    }
+
+    /// Activates when the widget's container is clicked, or activated from the keyboard.
+    ///
+    /// Enter and Space activate the widget. The Right arrow activates a collapsed widget, and
+    /// the Left arrow activates a revealed widget; or the reverse in right-to-left locales.
+    pub fn connect_clicked<F: Fn(gtk::Revealer) + 'static>(&self, func: F) {
+        self.clicked.borrow_mut().push(Rc::new(func));
+    }
+
+    /// Activates after the child is expanded or collapsed, by the user or programmatically.
+    ///
+    /// The callback receives whether the child is now revealed.
+    pub fn connect_toggled<F: Fn(bool) + 'static>(&self, func: F) {
+        let revealed = Cell::new(self.revealer.reveals_child());
+        self.revealer.connect_reveal_child_notify(move |revealer| {
+            let reveals = revealer.reveals_child();
+            if revealed.replace(reveals) != reveals {
+                func(reveals);
+            }
+        });
+    }
+
+    /// Defines how the child widget is generated when the button is expanded with `expand` or
+    /// `toggle`, if it has not been generated already.
+    pub fn set_generator<F: FnMut() -> gtk::Widget + 'static>(&self, func: F) {
+        *self.generator.borrow_mut() = Some(Box::new(func));
+    }
+
+    /// Whether the child widget is revealed, or is being revealed.
+    pub fn is_revealed(&self) -> bool { self.revealer.reveals_child() }
+
+    /// Reveals the child widget, generating it with the generator if it is missing.
+    pub fn expand(&self) { expand(&self.revealer, &self.generator); }
+
+    /// Hides the child widget.
+    pub fn collapse(&self) { self.revealer.set_reveal_child(false); }
+
+    /// Collapses the child widget if it is revealed, or expands it otherwise.
+    pub fn toggle(&self) { toggle(&self.revealer, &self.generator); }
+
+    /// Reveals an inner child, and generates it if it is missing.
+    pub fn reveal<F: FnMut() -> gtk::Widget>(&self, mut func: F) -> bool {
+        let reveal = if self.revealer.reveals_child() {
+            false
+        } else {
+            if self.revealer.child().is_none() {
+                self.revealer.add(&func());
+            }
+
+            true
+        };
+
+        self.revealer.set_reveal_child(reveal);
+        reveal
+    }
+
+    /// Defines the revealed status of the child widget.
+    pub fn set_reveal_child(&self, reveal: bool) { self.revealer.set_reveal_child(reveal); }
+
+    /// If the button has already generated a child widget, destroy it.
+    pub fn destroy_revealed(&self) {
+        if let Some(child) = self.revealer.child() {
+            unsafe { child.destroy() }
+        }
+    }
+}
+
+/// Invokes the clicked callbacks of the button, or toggles it if none are connected.
+///
+/// Returns `false` if there were no callbacks to invoke.
+fn activate(revealer: &gtk::Revealer, clicked: &Clicked, generator: &Generator) -> bool {
+    // The callbacks are cloned, so that they may connect further callbacks.
+    let callbacks = clicked.borrow().clone();
+
+    if callbacks.is_empty() {
+        toggle(revealer, generator);
+        return false;
+    }
+
+    for func in callbacks {
+        func(revealer.clone());
+    }
+
+    true
+}
+
+/// Reveals the child widget, generating it with the generator if it is missing.
+fn expand(revealer: &gtk::Revealer, generator: &Generator) {
+    if revealer.child().is_none() {
+        // The generator is taken while it runs, in case it expands the button itself.
+        let taken = generator.borrow_mut().take();
+        if let Some(mut func) = taken {
+            revealer.add(&func());
+
+            let mut slot = generator.borrow_mut();
+            if slot.is_none() {
+                *slot = Some(func);
+            }
+        }
+    }
+
+    revealer.set_reveal_child(true);
+}
+
+/// Collapses the child widget if it is revealed, or expands it otherwise.
+fn toggle(revealer: &gtk::Revealer, generator: &Generator) {
+    if revealer.reveals_child() {
+        revealer.set_reveal_child(false);
+    } else {
+        expand(revealer, generator);
+    }
+}
@@ -0,0 +1,187 @@
+//! Persistence of the expansion state of `RevealingButton`s across sessions.
+
+use super::RevealingButton;
+use gio::prelude::*;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Where the expansion state of `RevealingButton`s is stored, by the stable ids of buttons.
+#[derive(Clone)]
+pub enum ExpansionStore {
+    /// A string array key of a GSettings schema, containing the ids of expanded buttons.
+    Settings { settings: gio::Settings, key: String },
+    /// A group of a keyfile, containing a boolean key for each button by its id.
+    ///
+    /// The keyfile and its directory are created when the state is first stored.
+    KeyFile { path: PathBuf, group: String },
+}
+
+impl ExpansionStore {
+    pub fn settings(settings: &gio::Settings, key: &str) -> Self {
+        ExpansionStore::Settings { settings: settings.clone(), key: key.to_owned() }
+    }
+
+    pub fn key_file(path: impl Into<PathBuf>, group: &str) -> Self {
+        ExpansionStore::KeyFile { path: path.into(), group: group.to_owned() }
+    }
+
+    /// Whether the id may identify a button in the store.
+    ///
+    /// The id of a button in a keyfile is a key, which must not be empty, nor contain `=`,
+    /// `[`, `]`, or line breaks.
+    fn accepts(&self, id: &str) -> bool {
+        match self {
+            ExpansionStore::Settings { .. } => true,
+            ExpansionStore::KeyFile { .. } => {
+                !id.is_empty() && !id.contains(|c| matches!(c, '=' | '[' | ']' | '\n' | '\r'))
+            }
+        }
+    }
+
+    /// Whether the button with the id was expanded when it was last stored.
+    fn is_expanded(&self, id: &str) -> bool {
+        match self {
+            ExpansionStore::Settings { settings, key } => {
+                settings.strv(key).iter().any(|expanded| expanded.as_str() == id)
+            }
+            ExpansionStore::KeyFile { path, group } => {
+                load_key_file(path).map_or(false, |file| file.boolean(group, id).unwrap_or(false))
+            }
+        }
+    }
+
+    /// Stores whether the button with the id is expanded.
+    fn set_expanded(&self, id: &str, expanded: bool) {
+        match self {
+            ExpansionStore::Settings { settings, key } => {
+                let mut ids = settings.strv(key);
+                ids.retain(|current| current.as_str() != id);
+
+                let mut ids = ids.iter().map(|id| id.as_str()).collect::<Vec<&str>>();
+                if expanded {
+                    ids.push(id);
+                }
+
+                if let Err(why) = settings.set_strv(key, &ids) {
+                    error!("failed to write expansion state to {} setting: {}", key, why);
+                }
+            }
+            ExpansionStore::KeyFile { path, group } => {
+                let file = match load_key_file(path) {
+                    Some(file) => file,
+                    None => return,
+                };
+
+                file.set_boolean(group, id, expanded);
+
+                if let Some(parent) = path.parent() {
+                    if let Err(why) = fs::create_dir_all(parent) {
+                        error!("failed to create {}: {}", parent.display(), why);
+                        return;
+                    }
+                }
+
+                if let Err(why) = file.save_to_file(path) {
+                    error!("failed to write expansion state to {}: {}", path.display(), why);
+                }
+            }
+        }
+    }
+}
+
+impl RevealingButton {
+    /// Persists the expansion state of the button in the store, by a stable id.
+    ///
+    /// The button is expanded immediately if it was expanded when the state was last stored,
+    /// so the generator must be set with `set_generator` beforehand. A button which has
+    /// neither a child nor a generator is not expanded. The state is stored whenever the
+    /// button is expanded or collapsed thereafter.
+    ///
+    /// With a keyfile, the id is used as a key, so it must not be empty, nor contain `=`, `[`,
+    /// `]`, or line breaks. The state of a button with such an id is not persisted.
+    pub fn persist_expansion(&self, id: &str, store: &ExpansionStore) {
+        if !store.accepts(id) {
+            error!("cannot persist expansion state with an invalid keyfile key: {:?}", id);
+            return;
+        }
+
+        if store.is_expanded(id) {
+            if self.revealer.child().is_none() && self.generator.borrow().is_none() {
+                warn!("cannot restore expansion of {} without a child or a generator", id);
+            } else {
+                self.expand();
+            }
+        }
+
+        let id = id.to_owned();
+        let store = store.clone();
+        self.connect_toggled(move |expanded| store.set_expanded(&id, expanded));
+    }
+}
+
+/// Loads the keyfile, or creates an empty keyfile if it does not exist yet.
+fn load_key_file(path: &Path) -> Option<glib::KeyFile> {
+    let file = glib::KeyFile::new();
+
+    match file.load_from_file(path, glib::KeyFileFlags::KEEP_COMMENTS) {
+        Ok(()) => Some(file),
+        Err(why) if why.matches(glib::FileError::Noent) => Some(file),
+        Err(why) => {
+            error!("failed to read expansion state from {}: {}", path.display(), why);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpansionStore;
+    use std::fs;
+
+    #[test]
+    fn key_file_round_trip() {
+        let path = std::env::temp_dir()
+            .join(format!("gtk-extras-expansion-{}.ini", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let store = ExpansionStore::key_file(path.clone(), "expanded");
+        assert!(!store.is_expanded("audio"));
+
+        store.set_expanded("audio", true);
+        store.set_expanded("video", true);
+        store.set_expanded("video", false);
+
+        let reopened = ExpansionStore::key_file(path.clone(), "expanded");
+        assert!(reopened.is_expanded("audio"));
+        assert!(!reopened.is_expanded("video"));
+        assert!(!reopened.is_expanded("network"));
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn key_file_directory_is_created() {
+        let directory = std::env::temp_dir()
+            .join(format!("gtk-extras-expansion-{}", std::process::id()))
+            .join("nested");
+        let _ = fs::remove_dir_all(directory.parent().unwrap());
+
+        let store = ExpansionStore::key_file(directory.join("state.ini"), "expanded");
+        store.set_expanded("audio", true);
+        assert!(store.is_expanded("audio"));
+
+        let _ = fs::remove_dir_all(directory.parent().unwrap());
+    }
+
+    #[test]
+    fn key_file_rejects_invalid_keys() {
+        let store = ExpansionStore::key_file("state.ini", "expanded");
+        assert!(store.accepts("audio"));
+        assert!(!store.accepts(""));
+        assert!(!store.accepts("a=b"));
+        assert!(!store.accepts("[audio]"));
+        assert!(!store.accepts("audio\nvideo"));
+    }
+}
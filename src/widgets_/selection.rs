@@ -0,0 +1,213 @@
+//! Behavior shared by the `ImageSelection` and the `ColorSelection`.
+//!
+//! Both selections are owned by their widget rather than by their handle, and are released
+//! when the widget is destroyed, so closures only hold a `WeakSelection`. Focus enters a radio
+//! group on its active child, and moving the focus with the arrow, Home, or End keys selects
+//! the child which receives it. Either selection may be bound to a GSettings key, and follows
+//! changes of the key without invoking its callbacks.
+
+use gdk::keys::constants as key;
+use gio::{prelude::*, Settings};
+use glib::ObjectType;
+use gtk::prelude::*;
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+/// A reference to the widget and the state of a selection which keeps neither alive.
+pub(super) struct WeakSelection<W: ObjectType, I> {
+    container: glib::WeakRef<W>,
+    inner:     Weak<I>,
+}
+
+impl<W: ObjectType, I> WeakSelection<W, I> {
+    pub(super) fn new(container: &W, inner: &Rc<I>) -> Self {
+        Self { container: container.downgrade(), inner: Rc::downgrade(inner) }
+    }
+
+    /// The widget and the state of the selection, if both are still alive.
+    pub(super) fn upgrade(&self) -> Option<(W, Rc<I>)> {
+        Some((self.container.upgrade()?, self.inner.upgrade()?))
+    }
+}
+
+/// Lands the focus on the active child when it enters the container, if the child is visible.
+pub(super) fn enter_on_active<C, F>(container: &C, active: F)
+where
+    C: IsA<gtk::Container>,
+    F: Fn() -> Option<gtk::FlowBoxChild> + 'static,
+{
+    container.connect_focus(move |container, _| {
+        if container.focus_child().is_none() {
+            if let Some(child) = active().filter(|child| child.is_child_visible()) {
+                child.grab_focus();
+                return gtk::Inhibit(true);
+            }
+        }
+
+        gtk::Inhibit(false)
+    });
+}
+
+/// Selects the child when the arrow, Home, or End keys move the focus onto it, as in a radio
+/// group.
+///
+/// Focus which arrives by other means, such as by Tab into a selection whose active child is
+/// filtered out, does not change the selection.
+pub(super) fn select_on_focus<F: Fn() + 'static>(child: &gtk::FlowBoxChild, select: F) {
+    child.connect_focus_in_event(move |_, _| {
+        if navigating() {
+            select();
+        }
+
+        gtk::Inhibit(false)
+    });
+}
+
+/// Whether the event being handled is a key press of the arrow, Home, or End keys.
+fn navigating() -> bool {
+    let event = gtk::current_event().and_then(|event| event.downcast::<gdk::EventKey>().ok());
+    let event = match event {
+        Some(event) => event,
+        None => return false,
+    };
+
+    [
+        key::Up,
+        key::Down,
+        key::Left,
+        key::Right,
+        key::Home,
+        key::End,
+        key::KP_Up,
+        key::KP_Down,
+        key::KP_Left,
+        key::KP_Right,
+        key::KP_Home,
+        key::KP_End,
+    ]
+    .contains(&event.keyval())
+}
+
+/// Moves the focus to a newly-selected child if the focus is within the container.
+pub(super) fn follow_focus<C: IsA<gtk::Container>>(
+    container: &glib::WeakRef<C>,
+    child: &gtk::FlowBoxChild,
+) {
+    let focus_within =
+        container.upgrade().map_or(false, |container| container.focus_child().is_some());
+
+    if focus_within && !child.has_focus() {
+        child.grab_focus();
+    }
+}
+
+/// Relates the children to each other as members of the same radio group, replacing any
+/// group which they were previously members of.
+pub(super) fn relate_group<'a>(children: impl IntoIterator<Item = &'a gtk::FlowBoxChild>) {
+    let members = children.into_iter().filter_map(|child| child.accessible()).collect::<Vec<_>>();
+
+    let group = atk::Relation::new(&members, atk::RelationType::MemberOf);
+
+    for member in &members {
+        if let Some(relations) = member.ref_relation_set() {
+            if let Some(previous) = relations.relation_by_type(atk::RelationType::MemberOf) {
+                relations.remove(&previous);
+            }
+
+            relations.add(&group);
+        }
+    }
+}
+
+/// Binds a selection to a GSettings key, in both directions.
+///
+/// Each event is associated with a value of the key by `values`. A single selection binds to
+/// a string or enum key, and a `multiple` selection binds to a string array key, which
+/// contains the values of every selected event. `sync` is invoked with the events of the
+/// current value immediately, and whenever the key is changed, until `widget` is destroyed.
+///
+/// Returns the function which writes the selected events to the key.
+pub(super) fn bind_settings<T, S>(
+    widget: &impl IsA<gtk::Widget>,
+    settings: &Settings,
+    key: &str,
+    values: &[(T, &str)],
+    multiple: bool,
+    sync: S,
+) -> impl Fn(&[T])
+where
+    T: Clone + PartialEq + 'static,
+    S: Fn(&[T]) + 'static,
+{
+    let values = values
+        .iter()
+        .map(|(event, value)| (event.clone(), (*value).to_owned()))
+        .collect::<Vec<_>>();
+
+    let values = Rc::new(values);
+
+    sync(&read_settings(settings, key, &values, multiple));
+
+    let values_ = values.clone();
+    let handler = settings.connect_changed(Some(key), move |settings, key| {
+        sync(&read_settings(settings, key, &values_, multiple));
+    });
+
+    let binding = RefCell::new(Some((settings.clone(), handler)));
+    widget.connect_destroy(move |_| {
+        if let Some((settings, handler)) = binding.borrow_mut().take() {
+            settings.disconnect(handler);
+        }
+    });
+
+    let settings = settings.clone();
+    let key = key.to_owned();
+    move |selected| {
+        let selected =
+            selected.iter().filter_map(|event| value_of(&values, event)).collect::<Vec<&str>>();
+
+        let result = if multiple {
+            settings.set_strv(&key, &selected)
+        } else if let Some(value) = selected.first() {
+            settings.set_string(&key, value)
+        } else {
+            return;
+        };
+
+        if let Err(why) = result {
+            error!("failed to write selection to {} setting: {}", key, why);
+        }
+    }
+}
+
+/// The events associated with the current value of the key.
+fn read_settings<T: Clone>(
+    settings: &Settings,
+    key: &str,
+    values: &[(T, String)],
+    multiple: bool,
+) -> Vec<T> {
+    if multiple {
+        let current = settings.strv(key);
+        values
+            .iter()
+            .filter(|(_, value)| current.iter().any(|current| current.as_str() == value))
+            .map(|(event, _)| event.clone())
+            .collect()
+    } else {
+        let current = settings.string(key);
+        values
+            .iter()
+            .find(|(_, value)| current.as_str() == value)
+            .map(|(event, _)| event.clone())
+            .into_iter()
+            .collect()
+    }
+}
+
+/// The value of the key which is associated with the event.
+fn value_of<'a, T: PartialEq>(values: &'a [(T, String)], event: &T) -> Option<&'a str> {
+    values.iter().find(|(current, _)| current == event).map(|(_, value)| value.as_str())
+}